@@ -0,0 +1,70 @@
+//! Compare [`Stabby::merge`] with collecting the intervals of two indexes
+//! and building a new one.
+//!
+//! Run with `cargo bench --bench merge`, optionally giving the number of
+//! intervals in each index, which defaults to 500000.
+
+use std::time::{Duration, Instant};
+
+use stabby::{Interval, Stabby};
+
+/// A xorshift generator, so that runs are repeatable.
+struct Rng(u64);
+
+impl Rng {
+    fn rnd(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn random_intervals(rng: &mut Rng, n: usize) -> Vec<Interval> {
+    let mut xs: Vec<Interval> = (0..n)
+        .map(|_| {
+            let first = rng.rnd() % (100 * n as u64);
+            Interval::new(first, first + rng.rnd() % 5000)
+        })
+        .collect();
+    xs.sort();
+    xs.dedup();
+    xs
+}
+
+/// The shortest of several timings of `f`.
+fn best_of<T>(runs: usize, mut f: impl FnMut() -> T) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let t = Instant::now();
+            std::hint::black_box(f());
+            t.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let n: usize = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(500_000);
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let a = Stabby::new(&random_intervals(&mut rng, n));
+    let b = Stabby::new(&random_intervals(&mut rng, n));
+
+    let merge = best_of(5, || Stabby::merge(&a, &b));
+    let rebuild = best_of(5, || {
+        let mut xs: Vec<Interval> = a.iter().chain(b.iter()).collect();
+        xs.sort_unstable();
+        xs.dedup();
+        Stabby::new(&xs)
+    });
+    println!("two indexes of {} intervals each", n);
+    println!("merge:   {:?}", merge);
+    println!("rebuild: {:?}", rebuild);
+    println!(
+        "merge takes {:.0}% of the time of a rebuild",
+        100.0 * merge.as_secs_f64() / rebuild.as_secs_f64()
+    );
+}
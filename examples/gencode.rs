@@ -9,7 +9,7 @@ fn get_gene_name(rec: &noodles::gtf::Record) -> String {
             return e.value().to_string();
         }
     }
-    return "*".to_string();
+    "*".to_string()
 }
 
 fn main() -> std::io::Result<()> {
@@ -0,0 +1,339 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    dense::{DenseInterval, DenseLookup, DenseStabby},
+    domain::{Domain, DomainMap, FromPoints},
+    dynamic::merge,
+    sparse::{
+        domain_points, rank_all, stab_interval_with, stab_with, stabs_with, to_sparse_intervals,
+        SMALL_MAX,
+    },
+    DynamicStabby, Interval, Stabby, StabbyError,
+};
+
+/// The smallest number of pending intervals that triggers a rebuild.
+const PENDING_MIN: usize = 64;
+
+/// An index over intervals that arrive in order of their start, as with
+/// event logs or alignments streamed from a sorted file.
+///
+/// An interval that starts after every indexed interval has ended is
+/// added in place: the domain gains its endpoints and the dense structure
+/// grows to cover them, in constant time.
+///
+/// An interval that overlaps the indexed ones is instead held with the
+/// other pending intervals in a [`DynamicStabby`], so queries consult its
+/// O(log n) levels as well as the index. When the pending intervals come
+/// to number a quarter of the index (or 64, if that is more), everything
+/// is rebuilt into a single index. Each interval is therefore rebuilt into
+/// the index amortized O(1) times, besides the O(log n) times it is rebuilt
+/// within the pending set.
+///
+/// Intervals must be appended with non-decreasing starts; appending one
+/// that starts before an interval already present is an error. Like
+/// [`Stabby`], an `AppendStabby` holds a set: appending an interval that
+/// is already present has no effect.
+///
+pub struct AppendStabby {
+    domain: Vec<u64>,
+    dense: DenseStabby,
+    indexed: usize,
+    pending: DynamicStabby,
+    max_first: Option<u64>,
+}
+
+impl Default for AppendStabby {
+    fn default() -> Self {
+        AppendStabby::new()
+    }
+}
+
+impl AppendStabby {
+    /// Create a new, empty, index.
+    ///
+    pub fn new() -> AppendStabby {
+        AppendStabby {
+            domain: vec![0],
+            dense: DenseStabby::new(1, &[]),
+            indexed: 0,
+            pending: DynamicStabby::new(),
+            max_first: None,
+        }
+    }
+
+    /// The number of intervals in the index.
+    ///
+    pub fn len(&self) -> usize {
+        self.indexed + self.pending.len()
+    }
+
+    /// Test whether the index is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append an interval, returning `true` if it was not already present.
+    ///
+    /// Returns [`StabbyError::OutOfOrder`], leaving the index unchanged, if
+    /// the interval starts before the last interval appended.
+    ///
+    pub fn push(&mut self, x: Interval) -> Result<bool, StabbyError> {
+        self.check_order(x.first)?;
+        Ok(self.push_unchecked(x))
+    }
+
+    /// Append a batch of intervals, returning the number that were not
+    /// already present.
+    ///
+    /// The batch is checked before anything is appended, so if any interval
+    /// is out of order, the index is left unchanged.
+    ///
+    pub fn extend<I: IntoIterator<Item = Interval>>(
+        &mut self,
+        xs: I,
+    ) -> Result<usize, StabbyError> {
+        let xs: Vec<Interval> = xs.into_iter().collect();
+        let mut prev = self.max_first;
+        for x in xs.iter() {
+            if let Some(p) = prev {
+                if x.first < p {
+                    return Err(StabbyError::OutOfOrder {
+                        previous: p,
+                        found: x.first,
+                    });
+                }
+            }
+            prev = Some(x.first);
+        }
+        Ok(xs.into_iter().filter(|x| self.push_unchecked(*x)).count())
+    }
+
+    /// Check that an interval starting at `first` may be appended.
+    pub(crate) fn check_order(&self, first: u64) -> Result<(), StabbyError> {
+        match self.max_first {
+            Some(previous) if first < previous => Err(StabbyError::OutOfOrder {
+                previous,
+                found: first,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn push_unchecked(&mut self, x: Interval) -> bool {
+        let max_end = *self.domain.last().unwrap();
+        if self.indexed == 0 || x.first > max_end {
+            self.append(x);
+            return true;
+        }
+        if self.max_first == Some(x.first) && self.contains_last_start(&x) {
+            return false;
+        }
+        self.max_first = Some(x.first);
+        self.pending.insert(x);
+        if self.pending.len() >= PENDING_MIN.max(self.indexed / 4) {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// Test for an interval sharing its start with the last one appended.
+    fn contains_last_start(&self, x: &Interval) -> bool {
+        self.pending.contains(x) || stab_with(&self.domain, &self.dense, x.first).contains(x)
+    }
+
+    /// Add an interval that starts after every indexed interval has ended.
+    fn append(&mut self, x: Interval) {
+        for p in [x.first, x.last] {
+            if p > *self.domain.last().unwrap() {
+                self.domain.push(p);
+            }
+        }
+        let y = DenseInterval::new(self.domain.rank(x.first) * 2, self.domain.rank(x.last) * 2);
+        self.dense.append(y);
+        self.indexed += 1;
+        self.max_first = Some(x.first);
+    }
+
+    /// Rebuild the index to include the pending intervals.
+    fn rebuild(&mut self) {
+        self.retain(|_| true);
+    }
+
+    /// Rebuild the index with only the intervals satisfying a predicate.
+    /// The order of appends is still checked against the intervals removed.
+    ///
+    pub(crate) fn retain(&mut self, pred: impl Fn(&Interval) -> bool) {
+        let mut xs = self.intervals();
+        xs.retain(pred);
+        self.pending = DynamicStabby::new();
+        self.domain = domain_points(&xs);
+        let ys = rank_all(&self.domain, &xs);
+        let y_max = ys.iter().map(|y| y.last).max().unwrap_or(0);
+        self.dense = DenseStabby::new(y_max + 1, &ys);
+        self.indexed = xs.len();
+    }
+
+    /// Test if a position stabs any intervals.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        stabs_with(&self.domain, &self.dense, q) || self.pending.stabs(q)
+    }
+
+    /// Retrieve the list of intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        merge(
+            stab_with(&self.domain, &self.dense, q),
+            self.pending.stab(q),
+        )
+    }
+
+    /// Find the list of intervals that intersect the given query interval.
+    /// The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        merge(
+            stab_interval_with(&self.domain, &self.dense, q),
+            self.pending.stab_interval(q),
+        )
+    }
+
+    /// Recover the intervals in the index, in sorted order.
+    ///
+    pub(crate) fn intervals(&self) -> Vec<Interval> {
+        let xs = to_sparse_intervals(&self.domain, &self.dense.intervals());
+        merge(xs, self.pending.intervals())
+    }
+
+    /// Convert the index into an ordinary [`Stabby`].
+    ///
+    pub fn into_stabby(mut self) -> Stabby {
+        if !self.pending.is_empty() {
+            self.rebuild();
+        }
+        if self.indexed <= SMALL_MAX {
+            let xs = to_sparse_intervals(&self.domain, &self.dense.intervals());
+            return Stabby::small(xs);
+        }
+        Stabby::from_layers(Domain::from_points(self.domain), self.dense)
+    }
+}
+
+impl From<Stabby> for AppendStabby {
+    fn from(value: Stabby) -> Self {
+        let (domain, dense) = value.into_layers();
+        let intervals = dense.intervals();
+        let max_first = intervals.last().map(|y| domain[y.first / 2]);
+        AppendStabby {
+            domain,
+            dense,
+            indexed: intervals.len(),
+            pending: DynamicStabby::new(),
+            max_first,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::test_util::MiniRng;
+
+    fn check(a: &AppendStabby, oracle: &BTreeSet<Interval>, q_max: u64) {
+        assert_eq!(a.len(), oracle.len());
+        let s = Stabby::new(&oracle.iter().copied().collect::<Vec<Interval>>());
+        for q in 0..q_max {
+            assert_eq!(a.stabs(q), s.stabs(q));
+            assert_eq!(a.stab(q), s.stab(q));
+            let qi = Interval::new(q, q + 9);
+            assert_eq!(a.stab_interval(&qi), s.stab_interval(&qi));
+        }
+    }
+
+    #[test]
+    fn test_disjoint_appends() {
+        let mut a = AppendStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut first = 0;
+        for _ in 0..200 {
+            let x = Interval::new(first, first + first % 7);
+            assert!(a.push(x).unwrap());
+            oracle.insert(x);
+            first += first % 7 + 1 + first % 3;
+        }
+        assert!(a.pending.is_empty());
+        check(&a, &oracle, first + 10);
+    }
+
+    #[test]
+    fn test_overlapping_appends() {
+        let mut a = AppendStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut rng = MiniRng::new(23);
+        let mut first = 0;
+        for i in 0..2000 {
+            first += rng.rnd() % 4;
+            let x = Interval::new(first, first + rng.rnd() % 20);
+            assert_eq!(a.push(x).unwrap(), oracle.insert(x));
+            if i % 400 == 0 {
+                check(&a, &oracle, first + 30);
+            }
+        }
+        check(&a, &oracle, first + 30);
+        let s = a.into_stabby();
+        assert_eq!(
+            s.intervals(),
+            oracle.iter().copied().collect::<Vec<Interval>>()
+        );
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut a = AppendStabby::new();
+        a.push(Interval::new(10, 20)).unwrap();
+        a.push(Interval::new(15, 16)).unwrap();
+        let res = a.push(Interval::new(12, 30));
+        assert!(matches!(
+            res,
+            Err(StabbyError::OutOfOrder {
+                previous: 15,
+                found: 12
+            })
+        ));
+        let res = a.extend([Interval::new(40, 50), Interval::new(30, 35)]);
+        assert!(matches!(
+            res,
+            Err(StabbyError::OutOfOrder {
+                previous: 40,
+                found: 30
+            })
+        ));
+        assert_eq!(a.len(), 2);
+        assert_eq!(
+            a.extend([Interval::new(15, 16), Interval::new(40, 50)])
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_from_stabby() {
+        let s = Stabby::new(&[Interval::new(10, 100), Interval::new(20, 30)]);
+        let mut a = AppendStabby::from(s);
+        assert!(a.push(Interval::new(5, 6)).is_err());
+        assert!(a.push(Interval::new(150, 160)).unwrap());
+        assert_eq!(
+            a.stab_interval(&Interval::new(25, 155)),
+            vec![
+                Interval::new(10, 100),
+                Interval::new(20, 30),
+                Interval::new(150, 160)
+            ]
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! The map and set types used internally: hash tables where `std` is
+//! available, and B-trees from `alloc` where it is not.
+
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{BTreeMap as Map, BTreeSet as Set};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap as Map, HashSet as Set};
+
+/// An estimate of the memory a map holds on the heap, in bytes, not
+/// counting anything its keys and values own.
+///
+#[cfg(feature = "std")]
+pub(crate) fn map_heap_bytes<K, V>(m: &Map<K, V>) -> usize {
+    // A table has a power of two buckets, at most 7/8 of them usable, and
+    // a control byte for each, plus a group's worth more.
+    const GROUP: usize = 16;
+    if m.capacity() == 0 {
+        return 0;
+    }
+    let buckets = (m.capacity() * 8 / 7).next_power_of_two();
+    buckets * (size_of::<(K, V)>() + 1) + GROUP
+}
+
+/// An estimate of the memory a map holds on the heap, in bytes, not
+/// counting anything its keys and values own.
+///
+#[cfg(not(feature = "std"))]
+pub(crate) fn map_heap_bytes<K, V>(m: &Map<K, V>) -> usize {
+    // Leaves hold up to 11 entries, and are assumed to be full.
+    const B: usize = 11;
+    let node = B * (size_of::<K>() + size_of::<V>()) + size_of::<usize>() + 4;
+    m.len().div_ceil(B) * node
+}
@@ -0,0 +1,284 @@
+use alloc::vec::Vec;
+
+use crate::{domain::DomainMap, Interval, Stabby, StabbyError};
+
+/// A convention for writing intervals as pairs of numbers, convertible to
+/// and from the closed, 0-based [`Interval`] the indexes hold.
+///
+/// Converting is lossless where it succeeds, and fails only where the
+/// interval has no equivalent in the other convention.
+///
+pub trait Coordinates: Copy {
+    /// Convert to the closed, 0-based form.
+    fn to_interval(&self) -> Result<Interval, StabbyError>;
+
+    /// Convert from the closed, 0-based form.
+    fn from_interval(x: &Interval) -> Result<Self, StabbyError>;
+
+    /// Convert a single position in this convention to a 0-based one.
+    fn to_position(p: u64) -> Result<u64, StabbyError>;
+}
+
+impl Coordinates for Interval {
+    fn to_interval(&self) -> Result<Interval, StabbyError> {
+        if self.first > self.last {
+            return Err(StabbyError::InvalidCoordinates {
+                start: self.first,
+                end: self.last,
+                reason: "start is after end",
+            });
+        }
+        Ok(*self)
+    }
+
+    fn from_interval(x: &Interval) -> Result<Self, StabbyError> {
+        Ok(*x)
+    }
+
+    fn to_position(p: u64) -> Result<u64, StabbyError> {
+        Ok(p)
+    }
+}
+
+/// An interval in 0-based, half-open coordinates, as in BED files: the
+/// first base of a sequence is 0, and `end` is one past the last position.
+///
+/// An empty interval, with `start == end`, has no closed equivalent, nor
+/// does a closed interval ending at `u64::MAX` have a half-open one.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZeroHalfOpen {
+    /// The first position.
+    pub start: u64,
+
+    /// One past the last position.
+    pub end: u64,
+}
+
+impl ZeroHalfOpen {
+    /// Create a new half-open interval.
+    pub fn new(start: u64, end: u64) -> ZeroHalfOpen {
+        ZeroHalfOpen { start, end }
+    }
+}
+
+impl Coordinates for ZeroHalfOpen {
+    fn to_interval(&self) -> Result<Interval, StabbyError> {
+        if self.start >= self.end {
+            return Err(StabbyError::InvalidCoordinates {
+                start: self.start,
+                end: self.end,
+                reason: "half-open interval is empty",
+            });
+        }
+        Ok(Interval::new(self.start, self.end - 1))
+    }
+
+    fn from_interval(x: &Interval) -> Result<Self, StabbyError> {
+        match x.last.checked_add(1) {
+            Some(end) => Ok(ZeroHalfOpen::new(x.first, end)),
+            None => Err(StabbyError::InvalidCoordinates {
+                start: x.first,
+                end: x.last,
+                reason: "interval ends too late to be written half-open",
+            }),
+        }
+    }
+
+    fn to_position(p: u64) -> Result<u64, StabbyError> {
+        Ok(p)
+    }
+}
+
+/// An interval in 1-based, closed coordinates, as in GTF and VCF files:
+/// the first base of a sequence is 1, and `end` is the last position.
+///
+/// Position 0 does not exist in this convention, and a closed 0-based
+/// interval ending at `u64::MAX` has no 1-based equivalent.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OneClosed {
+    /// The first position.
+    pub start: u64,
+
+    /// The last position.
+    pub end: u64,
+}
+
+impl OneClosed {
+    /// Create a new 1-based closed interval.
+    pub fn new(start: u64, end: u64) -> OneClosed {
+        OneClosed { start, end }
+    }
+}
+
+impl Coordinates for OneClosed {
+    fn to_interval(&self) -> Result<Interval, StabbyError> {
+        let err = |reason| StabbyError::InvalidCoordinates {
+            start: self.start,
+            end: self.end,
+            reason,
+        };
+        if self.start == 0 {
+            return Err(err("1-based positions start at 1"));
+        }
+        if self.start > self.end {
+            return Err(err("start is after end"));
+        }
+        Ok(Interval::new(self.start - 1, self.end - 1))
+    }
+
+    fn from_interval(x: &Interval) -> Result<Self, StabbyError> {
+        match x.last.checked_add(1) {
+            Some(end) => Ok(OneClosed::new(x.first + 1, end)),
+            None => Err(StabbyError::InvalidCoordinates {
+                start: x.first,
+                end: x.last,
+                reason: "interval ends too late to be written 1-based",
+            }),
+        }
+    }
+
+    fn to_position(p: u64) -> Result<u64, StabbyError> {
+        p.checked_sub(1).ok_or(StabbyError::InvalidCoordinates {
+            start: p,
+            end: p,
+            reason: "1-based positions start at 1",
+        })
+    }
+}
+
+/// Convert a list of results back to the caller's convention.
+fn convert_all<C: Coordinates>(xs: Vec<Interval>) -> Result<Vec<C>, StabbyError> {
+    xs.iter().map(C::from_interval).collect()
+}
+
+impl Stabby {
+    /// Create a new index from intervals in any coordinate system. The
+    /// list must be in sorted order, and free of duplicates, which the
+    /// conversion preserves.
+    ///
+    /// ```rust
+    /// use stabby::{OneClosed, Stabby, ZeroHalfOpen};
+    ///
+    /// // BED records for the first two exons of a gene.
+    /// let exons = [ZeroHalfOpen::new(1000, 1200), ZeroHalfOpen::new(1500, 1700)];
+    /// let idx = Stabby::from_coordinates(&exons).unwrap();
+    ///
+    /// // The last base of the first exon, as a VCF position.
+    /// assert_eq!(idx.stab_as::<OneClosed>(1200).unwrap(), vec![OneClosed::new(1001, 1200)]);
+    /// assert!(idx.stab_as::<ZeroHalfOpen>(1200).unwrap().is_empty());
+    /// ```
+    ///
+    pub fn from_coordinates<C: Coordinates>(xs: &[C]) -> Result<Stabby, StabbyError> {
+        let xs: Vec<Interval> = xs
+            .iter()
+            .map(|x| x.to_interval())
+            .collect::<Result<_, _>>()?;
+        Ok(Stabby::new(&xs))
+    }
+}
+
+impl<D: DomainMap> Stabby<D> {
+    /// Retrieve the intervals stabbed by a position, with the position and
+    /// the results in the given coordinate system.
+    ///
+    pub fn stab_as<C: Coordinates>(&self, q: u64) -> Result<Vec<C>, StabbyError> {
+        convert_all(self.stab(C::to_position(q)?))
+    }
+
+    /// Find the intervals that intersect a query, with the query and the
+    /// results in the same coordinate system.
+    ///
+    pub fn stab_interval_as<C: Coordinates>(&self, q: &C) -> Result<Vec<C>, StabbyError> {
+        convert_all(self.stab_interval(&q.to_interval()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: u64 = u64::MAX;
+
+    #[test]
+    fn test_conversions() {
+        let x = Interval::new(99, 199);
+        assert_eq!(
+            ZeroHalfOpen::from_interval(&x).unwrap(),
+            ZeroHalfOpen::new(99, 200)
+        );
+        assert_eq!(
+            OneClosed::from_interval(&x).unwrap(),
+            OneClosed::new(100, 200)
+        );
+        assert_eq!(ZeroHalfOpen::new(99, 200).to_interval().unwrap(), x);
+        assert_eq!(OneClosed::new(100, 200).to_interval().unwrap(), x);
+
+        // The extremes of each convention.
+        assert_eq!(
+            ZeroHalfOpen::new(0, 1).to_interval().unwrap(),
+            Interval::new(0, 0)
+        );
+        assert_eq!(
+            ZeroHalfOpen::new(0, MAX).to_interval().unwrap(),
+            Interval::new(0, MAX - 1)
+        );
+        assert_eq!(
+            OneClosed::new(MAX, MAX).to_interval().unwrap(),
+            Interval::new(MAX - 1, MAX - 1)
+        );
+        assert!(ZeroHalfOpen::new(5, 5).to_interval().is_err());
+        assert!(OneClosed::new(0, 5).to_interval().is_err());
+        assert!(OneClosed::new(6, 5).to_interval().is_err());
+        assert!(ZeroHalfOpen::from_interval(&Interval::new(0, MAX)).is_err());
+        assert!(OneClosed::from_interval(&Interval::new(3, MAX)).is_err());
+        assert!(OneClosed::to_position(0).is_err());
+
+        for x in [
+            Interval::new(0, 0),
+            Interval::new(7, 9),
+            Interval::new(0, MAX - 1),
+        ] {
+            let h = ZeroHalfOpen::from_interval(&x).unwrap();
+            assert_eq!(h.to_interval().unwrap(), x);
+            let o = OneClosed::from_interval(&x).unwrap();
+            assert_eq!(o.to_interval().unwrap(), x);
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let genes = [
+            OneClosed::new(1, 10),
+            OneClosed::new(11, 20),
+            OneClosed::new(15, 30),
+        ];
+        let idx = Stabby::from_coordinates(&genes).unwrap();
+        assert_eq!(idx.stab(0), vec![Interval::new(0, 9)]);
+        assert_eq!(idx.stab_as::<OneClosed>(10).unwrap(), vec![genes[0]]);
+        assert_eq!(idx.stab_as::<OneClosed>(11).unwrap(), vec![genes[1]]);
+        assert_eq!(
+            idx.stab_as::<ZeroHalfOpen>(10).unwrap(),
+            vec![ZeroHalfOpen::new(10, 20)]
+        );
+        assert_eq!(
+            idx.stab_interval_as(&ZeroHalfOpen::new(10, 15)).unwrap(),
+            vec![ZeroHalfOpen::new(10, 20), ZeroHalfOpen::new(14, 30)]
+        );
+        assert_eq!(
+            idx.stab_interval_as(&OneClosed::new(10, 14)).unwrap(),
+            vec![genes[0], genes[1]]
+        );
+        assert!(idx.stab_as::<OneClosed>(0).is_err());
+        assert!(Stabby::from_coordinates(&[ZeroHalfOpen::new(3, 3)]).is_err());
+
+        // Results that cannot be written in the caller's convention.
+        let wide = Stabby::new(&[Interval::new(0, MAX)]);
+        assert!(wide.stab_as::<ZeroHalfOpen>(5).is_err());
+        assert_eq!(
+            wide.stab_as::<Interval>(5).unwrap(),
+            vec![Interval::new(0, MAX)]
+        );
+    }
+}
@@ -1,22 +1,29 @@
-use std::{
-    collections::{HashMap, VecDeque},
-    hash::Hash,
-};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::hash::Hash;
 
-use crate::listy::{Listy, ListyElement};
+use crate::{
+    collections::Map,
+    listy::{Listy, ListyElement},
+};
 
+/// A closed interval over the dense domain, used with [`DenseStabby`].
 #[derive(Clone, Copy, Eq, PartialOrd, Ord, Default, Hash, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DenseInterval {
+    /// Lower bound of the interval
     pub first: usize,
+
+    /// Upper bound of the interval
     pub last: usize,
 }
 
 impl DenseInterval {
+    /// Create a new interval
     pub fn new(first: usize, last: usize) -> DenseInterval {
         DenseInterval { first, last }
     }
 
-    pub fn zero() -> DenseInterval {
+    pub(crate) fn zero() -> DenseInterval {
         DenseInterval { first: 0, last: 0 }
     }
 }
@@ -25,14 +32,11 @@ impl DenseInterval {
 ///
 fn make_smaller(
     items: &[DenseInterval],
-) -> (
-    Vec<DenseInterval>,
-    HashMap<DenseInterval, Vec<DenseInterval>>,
-) {
+) -> (Vec<DenseInterval>, Map<DenseInterval, Vec<DenseInterval>>) {
     let n = items.len();
     let mut i: usize = 0;
     let mut basic: Vec<DenseInterval> = Vec::new();
-    let mut smaller: HashMap<DenseInterval, Vec<DenseInterval>> = HashMap::new();
+    let mut smaller: Map<DenseInterval, Vec<DenseInterval>> = Map::new();
     while i < n {
         let mut j: usize = i;
         while j + 1 < n && items[j].first == items[j + 1].first {
@@ -40,7 +44,7 @@ fn make_smaller(
         }
         if j > i {
             let v = &items[i..j];
-            if v.len() > 0 {
+            if !v.is_empty() {
                 smaller.insert(items[j], Vec::from(v));
             }
         }
@@ -52,21 +56,37 @@ fn make_smaller(
 
 /// The DenseStabby data structure represents the set of intervals over the dense domain.
 ///
-#[derive(Debug)]
+/// This is the structure described by Schmidt, over positions `0..=q_max`.
+/// A [`Stabby`](crate::Stabby) builds one over the ranks of the endpoints
+/// of its intervals, but where the coordinates are already small and
+/// dense, it may be used directly.
+///
+/// ```rust
+/// use stabby::{DenseInterval, DenseStabby};
+///
+/// let idx = DenseStabby::new(10, &[DenseInterval::new(2, 6), DenseInterval::new(4, 9)]);
+/// assert_eq!(idx.stab(5), vec![DenseInterval::new(2, 6), DenseInterval::new(4, 9)]);
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DenseStabby {
-    smaller: HashMap<DenseInterval, Vec<DenseInterval>>,
-    start: Vec<Option<DenseInterval>>,
-    start2: Vec<Option<DenseInterval>>,
-    parent: HashMap<DenseInterval, DenseInterval>,
-    last: HashMap<DenseInterval, DenseInterval>,
-    left: HashMap<DenseInterval, DenseInterval>,
+    pub(crate) smaller: Map<DenseInterval, Vec<DenseInterval>>,
+    pub(crate) start: Vec<Option<DenseInterval>>,
+    pub(crate) start2: Vec<Option<DenseInterval>>,
+    pub(crate) parent: Map<DenseInterval, DenseInterval>,
+    pub(crate) last: Map<DenseInterval, DenseInterval>,
+    pub(crate) left: Map<DenseInterval, DenseInterval>,
 }
 
 impl DenseStabby {
+    /// Build the data structure over the positions `0..=q_max`. The list
+    /// of intervals must be in natural sorted order, free of duplicates,
+    /// and lie within those positions.
+    ///
     pub fn new(q_max: usize, items: &[DenseInterval]) -> DenseStabby {
         let (basic, smaller) = make_smaller(items);
         let mut event: Vec<Vec<DenseInterval>> = Vec::new();
-        event.resize_with(q_max + 1, || Vec::new());
+        event.resize_with(q_max + 1, Vec::new);
 
         for item in basic.iter() {
             event[item.last].push(*item);
@@ -79,12 +99,12 @@ impl DenseStabby {
         let mut start2: Vec<Option<DenseInterval>> = Vec::new();
         start2.resize(q_max + 1, None);
 
-        let mut parent: HashMap<DenseInterval, DenseInterval> = HashMap::new();
-        let mut last: HashMap<DenseInterval, DenseInterval> = HashMap::new();
-        let mut left: HashMap<DenseInterval, DenseInterval> = HashMap::new();
+        let mut parent: Map<DenseInterval, DenseInterval> = Map::new();
+        let mut last: Map<DenseInterval, DenseInterval> = Map::new();
+        let mut left: Map<DenseInterval, DenseInterval> = Map::new();
 
         let mut l: Listy<DenseInterval> = Listy::new();
-        let mut saved: HashMap<DenseInterval, ListyElement<DenseInterval>> = HashMap::new();
+        let mut saved: Map<DenseInterval, ListyElement<DenseInterval>> = Map::new();
         let mut rml: usize = 0;
 
         for q in 0..=q_max {
@@ -95,7 +115,9 @@ impl DenseStabby {
                 }
             }
             while let Some(a) = event[q].pop() {
-                if a.first == q {
+                // A point interval has both its events here: the first
+                // popped opens it, and the second closes it.
+                if a.first == q && !saved.contains_key(&a) {
                     start[q] = Some(a);
                     let ptr = l.push_back(a);
                     saved.insert(a, ptr);
@@ -115,7 +137,7 @@ impl DenseStabby {
                     last.insert(p, a);
 
                     if let Some(a_ptr) = saved.get(&a) {
-                        l.remove(&a_ptr);
+                        l.remove(a_ptr);
                     }
 
                     saved.remove(&a);
@@ -124,62 +146,155 @@ impl DenseStabby {
             while rml + 1 < basic.len() && basic[rml + 1].first <= q {
                 rml += 1;
             }
-            if basic[rml].first <= q {
-                start2[q] = Some(basic[rml]);
+            if let Some(b) = basic.get(rml) {
+                if b.first <= q {
+                    start2[q] = Some(*b);
+                }
             }
         }
 
         DenseStabby {
-            smaller: smaller,
-            start: start,
-            start2: start2,
-            parent: parent,
-            last: last,
-            left: left,
+            smaller,
+            start,
+            start2,
+            parent,
+            last,
+            left,
         }
     }
 
-    /// A quick test to see if a position is included in any intervals
-    /// without determining which specific intervals.
+    /// Add an interval that begins after every interval already present
+    /// has ended, growing the domain to `y.last + 2` positions.
+    ///
+    /// Such an interval neither contains nor is contained by any other, so
+    /// it becomes a new root: the sweep in [`DenseStabby::new`] would leave
+    /// every existing entry unchanged, and write the new ones exactly as
+    /// they are written here.
+    ///
+    pub(crate) fn append(&mut self, y: DenseInterval) {
+        debug_assert!(self.parent.keys().all(|a| a.last < y.first));
+        let q_max = y.last + 1;
+        let fill = self.start2.last().copied().flatten();
+        self.start.resize(q_max + 1, None);
+        self.start2.resize(q_max + 1, fill);
+        for q in y.first..=y.last {
+            self.start[q] = Some(y);
+        }
+        for q in y.first..=q_max {
+            self.start2[q] = Some(y);
+        }
+        let zero = DenseInterval::zero();
+        self.parent.insert(y, zero);
+        if let Some(b) = self.last.get(&zero) {
+            self.left.insert(y, *b);
+        }
+        self.last.insert(zero, y);
+    }
+
+    /// Test if a position stabs any intervals.
+    ///
     pub fn stabs(&self, q: usize) -> bool {
-        q < self.start.len() && self.start[q].is_some()
+        DenseLookup::stabs(self, q)
     }
 
+    /// Retrieve the list of intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
     pub fn stab(&self, q: usize) -> Vec<DenseInterval> {
+        DenseLookup::stab(self, q)
+    }
+
+    /// Find the list of intervals that intersect the given query interval.
+    /// The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &DenseInterval) -> Vec<DenseInterval> {
+        DenseLookup::stab_interval(self, q)
+    }
+}
+
+/// The lookups on which the query algorithms depend. Abstracting them
+/// lets the same algorithms run over the in-memory structure and over
+/// serialized copies of it.
+///
+pub trait DenseLookup {
+    /// The size of the dense domain.
+    fn size(&self) -> usize;
+
+    /// The innermost interval containing position `q`, if any.
+    fn start(&self, q: usize) -> Option<DenseInterval>;
+
+    /// The basic interval with the greatest first position not after `q`.
+    fn start2(&self, q: usize) -> Option<DenseInterval>;
+
+    /// The parent of a basic interval; the zero interval for roots.
+    fn parent(&self, a: &DenseInterval) -> Option<DenseInterval>;
+
+    /// The sibling that ended immediately before a basic interval.
+    fn left(&self, a: &DenseInterval) -> Option<DenseInterval>;
+
+    /// The last child to end of a basic interval (or of the zero interval).
+    fn last(&self, a: &DenseInterval) -> Option<DenseInterval>;
+
+    /// The intervals sharing their first position with a basic interval,
+    /// in sorted order.
+    fn smaller(&self, a: &DenseInterval) -> impl DoubleEndedIterator<Item = DenseInterval> + '_;
+
+    /// Recover the intervals the structure was built from, in sorted order.
+    ///
+    /// Each basic interval is recorded in `start` at its own first position,
+    /// and the intervals it shadows in `smaller` share that position, so a
+    /// single pass over the dense domain visits them all.
+    fn intervals(&self) -> Vec<DenseInterval> {
         let mut res: Vec<DenseInterval> = Vec::new();
-        if q >= self.start.len() {
+        for p in 0..self.size() {
+            if let Some(a) = self.start(p) {
+                if a.first == p {
+                    res.extend(self.smaller(&a));
+                    res.push(a);
+                }
+            }
+        }
+        res
+    }
+
+    /// A quick test to see if a position is included in any intervals
+    /// without determining which specific intervals.
+    fn stabs(&self, q: usize) -> bool {
+        q < self.size() && self.start(q).is_some()
+    }
+
+    /// Find the intervals containing position `q`, in sorted order.
+    fn stab(&self, q: usize) -> Vec<DenseInterval> {
+        let mut res: Vec<DenseInterval> = Vec::new();
+        if q >= self.size() {
             return res;
         }
 
         let mut kew: VecDeque<DenseInterval> = VecDeque::new();
-        let mut ov: Option<DenseInterval> = self.start[q].clone();
+        // The point interval at position 0 coincides with the zero interval
+        // that roots the tree, so test for the root only among parents,
+        // which are never points.
+        let mut ov: Option<DenseInterval> = self.start(q);
         while let Some(v) = ov {
-            if v == DenseInterval::zero() {
-                break;
-            }
             kew.push_front(v);
-            ov = self.parent.get(&v).copied();
+            ov = self.parent(&v).filter(|p| *p != DenseInterval::zero());
         }
         while let Some(a) = kew.pop_back() {
             res.push(a);
-            match self.smaller.get(&a) {
-                None => {}
-                Some(s) => {
-                    for r in s.iter().rev() {
-                        if r.last < q {
-                            break;
-                        }
-                        res.push(*r);
-                    }
+            for r in self.smaller(&a).rev() {
+                if r.last < q {
+                    break;
                 }
+                res.push(r);
             }
-            let mut ot = self.left.get(&a);
+            let mut ot = self.left(&a);
             while let Some(t) = ot {
                 if t.last < q {
                     break;
                 }
-                kew.push_back(*t);
-                ot = self.last.get(t);
+                kew.push_back(t);
+                ot = self.last(&t);
             }
         }
 
@@ -187,72 +302,69 @@ impl DenseStabby {
         res
     }
 
-    pub fn stab_interval(&self, qi: &DenseInterval) -> Vec<DenseInterval> {
+    /// Find the intervals intersecting `qi`, in sorted order.
+    ///
+    /// These are the intervals containing `qi.first`, followed by those
+    /// starting after it but within `qi`. The latter are found by hopping
+    /// back through `start2` from `qi.last`, visiting just the positions
+    /// at which intervals start.
+    fn stab_interval(&self, qi: &DenseInterval) -> Vec<DenseInterval> {
         let lq = qi.first;
-        let rq = std::cmp::min(qi.last, self.start.len() - 1);
-        if lq >= self.start.len() || rq < lq {
+        if lq >= self.size() || qi.last < lq {
             return Vec::new();
         }
+        let rq = core::cmp::min(qi.last, self.size() - 1);
 
-        let mut ot: Option<DenseInterval> = self.start[lq];
-        if let Some(u) = self.start2[rq] {
-            if let Some(t) = ot {
-                if t.first < u.first {
-                    ot = Some(u);
-                }
-            } else {
-                ot = Some(u);
-            }
-        }
-
-        let mut res: Vec<DenseInterval> = Vec::new();
-        match ot {
-            None => {
-                return res;
-            }
-            Some(t) => {
-                if t.last < lq {
-                    return res;
-                }
-            }
-        }
+        let mut res: Vec<DenseInterval> = self.stab(lq);
 
-        let mut kew: VecDeque<DenseInterval> = VecDeque::new();
-        while let Some(t) = ot {
-            if t == DenseInterval::zero() {
+        let mut later: Vec<DenseInterval> = Vec::new();
+        let mut ou: Option<DenseInterval> = self.start2(rq);
+        while let Some(u) = ou {
+            if u.first <= lq {
                 break;
             }
-            kew.push_front(t);
-            ot = self.parent.get(&t).copied();
+            later.push(u);
+            later.extend(self.smaller(&u).rev());
+            ou = self.start2(u.first - 1);
         }
+        later.reverse();
+        res.extend(later);
+        res
+    }
+}
 
-        while let Some(a) = kew.pop_back() {
-            res.push(a);
+impl DenseLookup for DenseStabby {
+    fn size(&self) -> usize {
+        self.start.len()
+    }
 
-            match self.smaller.get(&a) {
-                None => {}
-                Some(s) => {
-                    for r in s.iter().rev() {
-                        if r.last < lq {
-                            break;
-                        }
-                        res.push(*r);
-                    }
-                }
-            }
+    fn start(&self, q: usize) -> Option<DenseInterval> {
+        self.start[q]
+    }
 
-            ot = self.left.get(&a).copied();
-            while let Some(t) = ot {
-                if t.last < lq {
-                    break;
-                }
-                kew.push_back(t);
-                ot = self.last.get(&t).copied();
-            }
-        }
+    fn start2(&self, q: usize) -> Option<DenseInterval> {
+        self.start2[q]
+    }
 
-        res.reverse();
-        res
+    fn parent(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.parent.get(a).copied()
+    }
+
+    fn left(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.left.get(a).copied()
+    }
+
+    fn last(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.last.get(a).copied()
+    }
+
+    fn smaller(&self, a: &DenseInterval) -> impl DoubleEndedIterator<Item = DenseInterval> + '_ {
+        self.smaller
+            .get(a)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .copied()
     }
 }
 
@@ -283,8 +395,8 @@ mod tests {
         let v = Vec::from([DenseInterval::new(1, 2)]);
         assert_eq!(s.smaller.len(), 1);
         assert_eq!(s.smaller.get(&w), Some(&v));
-        assert_eq!(s.stabs(3), true);
-        assert_eq!(s.stabs(0), false);
+        assert!(s.stabs(3));
+        assert!(!s.stabs(0));
         assert_eq!(
             s.stab(3),
             Vec::from([DenseInterval::new(1, 4), DenseInterval::new(3, 5)])
@@ -363,7 +475,7 @@ mod tests {
                     expected.push(*ivl);
                 }
             }
-            assert_eq!(s.stabs(q), expected.len() > 0);
+            assert_eq!(s.stabs(q), !expected.is_empty());
             assert_eq!(s.stab(q), expected);
         }
     }
@@ -552,6 +664,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_point_intervals() {
+        let src: Vec<DenseInterval> = Vec::from([
+            DenseInterval::new(2, 2),
+            DenseInterval::new(2, 6),
+            DenseInterval::new(4, 4),
+            DenseInterval::new(6, 6),
+            DenseInterval::new(6, 8),
+        ]);
+        let s = DenseStabby::new(9, &src);
+        for q in 0..=9 {
+            let expected: Vec<DenseInterval> = src
+                .iter()
+                .filter(|ivl| ivl.first <= q && q <= ivl.last)
+                .copied()
+                .collect();
+            assert_eq!(s.stab(q), expected);
+        }
+        assert_eq!(s.intervals(), src);
+    }
+
+    #[test]
+    fn test_point_at_zero() {
+        // The interval (0, 0) is equal to the zero interval at the root.
+        for src in [
+            Vec::from([DenseInterval::new(0, 0)]),
+            Vec::from([DenseInterval::new(0, 0), DenseInterval::new(2, 4)]),
+            Vec::from([DenseInterval::new(0, 0), DenseInterval::new(0, 2)]),
+        ] {
+            let s = DenseStabby::new(5, &src);
+            for q in 0..=5 {
+                let expected: Vec<DenseInterval> = src
+                    .iter()
+                    .filter(|ivl| ivl.first <= q && q <= ivl.last)
+                    .copied()
+                    .collect();
+                assert_eq!(s.stab(q), expected);
+                let expected: Vec<DenseInterval> =
+                    src.iter().filter(|ivl| ivl.first <= q).copied().collect();
+                assert_eq!(s.stab_interval(&DenseInterval::new(0, q)), expected);
+            }
+        }
+    }
+
     #[test]
     fn test_stabby_5() {
         let src: Vec<DenseInterval> = Vec::from([
@@ -573,4 +729,42 @@ mod tests {
             vec![DenseInterval::new(14, 16)]
         );
     }
+
+    #[test]
+    fn test_stab_interval_nested() {
+        // The last interval starting before the query ends before it, but
+        // the interval enclosing that one reaches into the query.
+        let src: Vec<DenseInterval> =
+            Vec::from([DenseInterval::new(2, 8), DenseInterval::new(4, 6)]);
+        let s = DenseStabby::new(9, &src);
+        assert_eq!(
+            s.stab_interval(&DenseInterval::new(7, 7)),
+            vec![DenseInterval::new(2, 8)]
+        );
+    }
+
+    #[test]
+    fn test_stab_interval_exhaustive() {
+        // Every set of intervals with distinct, even endpoints below 10.
+        let all: Vec<DenseInterval> = (0..5)
+            .flat_map(|a| (a + 1..5).map(move |b| DenseInterval::new(a * 2, b * 2)))
+            .collect();
+        for mask in 1u32..(1 << all.len()) {
+            let src: Vec<DenseInterval> = (0..all.len())
+                .filter(|i| (mask >> i) & 1 == 1)
+                .map(|i| all[i])
+                .collect();
+            let s = DenseStabby::new(9, &src);
+            for a in 0..=9 {
+                for b in a..=9 {
+                    let expected: Vec<DenseInterval> = src
+                        .iter()
+                        .filter(|ivl| ivl.first <= b && a <= ivl.last)
+                        .copied()
+                        .collect();
+                    assert_eq!(s.stab_interval(&DenseInterval::new(a, b)), expected);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,460 @@
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use ransel::{select::Select, set::ImpliedSet, sorted::Sorted};
+
+use crate::StabbyError;
+
+// The representation of the domain held by a `Stabby` unless another is
+// chosen: `ransel`'s sorted vector where `std` is available, and a plain
+// `Vec` where it is not. Both are searched by binary search.
+#[cfg(feature = "std")]
+pub(crate) type Domain = Sorted;
+#[cfg(not(feature = "std"))]
+pub(crate) type Domain = Vec<u64>;
+
+/// The mapping between the sparse domain of interval endpoints and
+/// the ranks from which the dense domain is built.
+///
+/// A [`Stabby`](crate::Stabby) consults its domain at the start of every
+/// query, so the choice of representation trades the space it takes
+/// against the speed of `rank`.
+///
+pub trait DomainMap {
+    /// The number of points in the domain.
+    fn count(&self) -> usize;
+
+    /// The number of points in the domain strictly less than `x`.
+    fn rank(&self, x: u64) -> usize;
+
+    /// The `i`-th smallest point in the domain, counting from 0.
+    fn select(&self, i: usize) -> u64;
+
+    /// An estimate of the memory the domain holds on the heap, in bytes,
+    /// for [`Stabby::heap_size_bytes`](crate::Stabby::heap_size_bytes).
+    /// By default, none.
+    fn heap_size_bytes(&self) -> usize {
+        0
+    }
+}
+
+/// A domain that can be built from a list of points, as needed by
+/// [`Stabby::with_domain`](crate::Stabby::with_domain).
+///
+pub trait FromPoints: DomainMap + Sized {
+    /// Build the domain from its points, which are sorted and distinct,
+    /// and include 0. The domain may hold other points besides.
+    ///
+    /// # Panics
+    ///
+    /// Panics where [`try_from_points`](FromPoints::try_from_points)
+    /// would fail.
+    ///
+    fn from_points(points: Vec<u64>) -> Self;
+
+    /// Build the domain from its points, failing if this representation
+    /// cannot hold them. By default, it never fails.
+    ///
+    fn try_from_points(points: Vec<u64>) -> Result<Self, StabbyError> {
+        Ok(Self::from_points(points))
+    }
+}
+
+/// The bound a domain must meet to be built into a [`Stabby`](crate::Stabby).
+///
+/// With the `rayon` feature, intervals are ranked in parallel, so the
+/// domain must be [`Sync`]; otherwise, every type meets it.
+///
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// The bound a domain must meet to be built into a [`Stabby`](crate::Stabby).
+///
+/// With the `rayon` feature, intervals are ranked in parallel, so the
+/// domain must be `Sync`; otherwise, every type meets it.
+///
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "rayon"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+#[cfg(feature = "std")]
+impl DomainMap for Sorted {
+    fn count(&self) -> usize {
+        ImpliedSet::count(self)
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        // `Rank::rank` first compares `x` with one past the last point,
+        // which overflows if that point is `u64::MAX`, so search here.
+        let (mut lo, mut hi) = (0, ImpliedSet::count(self));
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if Select::select(self, mid) < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        Select::select(self, i)
+    }
+
+    fn heap_size_bytes(&self) -> usize {
+        // The points are held in a vector of exactly their number.
+        ImpliedSet::count(self) * size_of::<u64>()
+    }
+}
+
+/// The sorted vector of points from `ransel`, searched by binary search,
+/// which is the default where `std` is available.
+///
+#[cfg(feature = "std")]
+impl FromPoints for Sorted {
+    fn from_points(points: Vec<u64>) -> Self {
+        Sorted::new(&points)
+    }
+}
+
+/// A domain held as a sorted vector of distinct points and searched by
+/// binary search. Unlike `Sorted`, it can grow.
+///
+impl DomainMap for Vec<u64> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        self.partition_point(|p| *p < x)
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        self[i]
+    }
+
+    fn heap_size_bytes(&self) -> usize {
+        self.capacity() * size_of::<u64>()
+    }
+}
+
+impl FromPoints for Vec<u64> {
+    fn from_points(points: Vec<u64>) -> Self {
+        points
+    }
+}
+
+/// A domain holding every integer from 0 up to some bound, so that `rank`
+/// and `select` are trivial and take no space at all.
+///
+/// This suits small, densely covered ranges of coordinates, where the
+/// structure of the dense domain can be used almost directly. The dense
+/// structure has two positions for every integer up to the largest
+/// endpoint, however, so it is a poor choice when endpoints are large,
+/// and cannot be built at all when there are more such positions than
+/// fit in a `usize`, as for an endpoint of `u64::MAX`.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdentityDomain {
+    count: usize,
+}
+
+impl IdentityDomain {
+    /// Create a domain holding the integers `0..count`.
+    ///
+    pub fn new(count: usize) -> IdentityDomain {
+        IdentityDomain { count }
+    }
+}
+
+impl DomainMap for IdentityDomain {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        x.min(self.count as u64) as usize
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        i as u64
+    }
+}
+
+impl FromPoints for IdentityDomain {
+    fn from_points(points: Vec<u64>) -> Self {
+        match Self::try_from_points(points) {
+            Ok(d) => d,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn try_from_points(points: Vec<u64>) -> Result<Self, StabbyError> {
+        let last = points.last().copied().unwrap_or(0);
+        // The dense domain needs two positions for every point.
+        usize::try_from(last)
+            .ok()
+            .and_then(|x| x.checked_add(1))
+            .filter(|n| n.checked_mul(2).is_some())
+            .map(IdentityDomain::new)
+            .ok_or(StabbyError::DomainOverflow { point: last })
+    }
+}
+
+/// A domain held as a sorted table laid out in Eytzinger (breadth-first)
+/// order, so that the first steps of every search touch the same few
+/// cache lines.
+///
+/// The table is padded to a complete binary tree, which makes the position
+/// of each point in sorted order a simple function of its position in the
+/// tree, at the cost of up to twice the space of a plain sorted table.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EytzingerDomain {
+    tree: Vec<u64>,
+    count: usize,
+    height: u32,
+}
+
+impl EytzingerDomain {
+    /// Lay out the points, which must be sorted and distinct.
+    ///
+    pub fn new(points: &[u64]) -> EytzingerDomain {
+        let mut height = 0;
+        while (1usize << height) - 1 < points.len() {
+            height += 1;
+        }
+        let size = (1usize << height) - 1;
+        let mut tree: Vec<u64> = vec![u64::MAX; size + 1];
+        let mut res = EytzingerDomain {
+            tree: Vec::new(),
+            count: points.len(),
+            height,
+        };
+        for (i, p) in points.iter().enumerate() {
+            tree[res.node(i)] = *p;
+        }
+        res.tree = tree;
+        res
+    }
+
+    /// The node of the tree holding the `i`-th point in sorted order.
+    fn node(&self, i: usize) -> usize {
+        // The in-order position i + 1 = (2j + 1) * 2^t for the j-th node
+        // of the level t above the leaves.
+        let m = i + 1;
+        let t = m.trailing_zeros();
+        let j = (m >> t) >> 1;
+        let d = self.height - 1 - t;
+        (1 << d) + j
+    }
+
+    /// The position in sorted order of the point held by a node.
+    fn position(&self, k: usize) -> usize {
+        let d = usize::BITS - 1 - k.leading_zeros();
+        let j = k - (1 << d);
+        ((2 * j + 1) << (self.height - 1 - d)) - 1
+    }
+}
+
+impl DomainMap for EytzingerDomain {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        let size = self.tree.len() - 1;
+        let mut k = 1;
+        while k <= size {
+            k = 2 * k + (self.tree[k] < x) as usize;
+        }
+        // Undo the final run of steps right, and the step left before it,
+        // to find the node holding the smallest point not less than `x`.
+        k >>= k.trailing_ones() + 1;
+        if k == 0 {
+            self.count
+        } else {
+            self.position(k).min(self.count)
+        }
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        self.tree[self.node(i)]
+    }
+
+    fn heap_size_bytes(&self) -> usize {
+        self.tree.capacity() * size_of::<u64>()
+    }
+}
+
+impl FromPoints for EytzingerDomain {
+    fn from_points(points: Vec<u64>) -> Self {
+        EytzingerDomain::new(&points)
+    }
+}
+
+/// Map a position in the sparse domain to the dense domain. Points of the
+/// domain map to even positions, and the gaps between them to the odd
+/// positions in between.
+///
+/// The domain must contain 0, so that every gap has a point below it.
+///
+pub(crate) fn to_dense<D: DomainMap>(domain: &D, x: u64) -> usize {
+    let r = domain.rank(x);
+    if r < domain.count() && domain.select(r) == x {
+        r * 2
+    } else {
+        r * 2 - 1
+    }
+}
+
+/// Map an (even) position in the dense domain back to the sparse domain.
+///
+pub(crate) fn to_sparse<D: DomainMap>(domain: &D, y: usize) -> u64 {
+    domain.select(y / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::MiniRng, Interval, Stabby};
+
+    fn check<D: DomainMap>(d: &D, points: &[u64]) {
+        assert_eq!(d.count(), points.len());
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(d.select(i), *p);
+            assert_eq!(d.rank(*p), i);
+            assert_eq!(d.rank(p + 1), i + 1);
+        }
+        assert_eq!(d.rank(u64::MAX), points.len());
+    }
+
+    #[test]
+    fn test_eytzinger() {
+        let mut rng = MiniRng::new(3);
+        for n in [0, 1, 2, 3, 7, 8, 100, 1023, 1024] {
+            let mut points: Vec<u64> = (0..n).map(|_| rng.rnd() % 100_000).collect();
+            points.sort_unstable();
+            points.dedup();
+            check(&EytzingerDomain::new(&points), &points);
+        }
+        let points: Vec<u64> = vec![0, 5, u64::MAX];
+        let d = EytzingerDomain::new(&points);
+        assert_eq!(d.rank(u64::MAX), 2);
+        assert_eq!(d.select(2), u64::MAX);
+    }
+
+    #[test]
+    fn test_identity() {
+        let d = IdentityDomain::from_points(vec![0, 3, 9]);
+        check(&d, &(0..10).collect::<Vec<u64>>());
+        assert!(matches!(
+            IdentityDomain::try_from_points(vec![0, u64::MAX]),
+            Err(StabbyError::DomainOverflow { point: u64::MAX })
+        ));
+        let xs: Vec<Interval> = (0..40).map(|i| Interval::new(i, u64::MAX)).collect();
+        assert!(Stabby::<IdentityDomain>::try_with_domain(&xs).is_err());
+    }
+
+    #[test]
+    fn test_max_endpoint() {
+        // Enough intervals that the full structure is built, so that the
+        // domain holds u64::MAX.
+        let mut xs: Vec<Interval> = (0..40).map(|i| Interval::new(i * 10, i * 10 + 5)).collect();
+        xs.push(Interval::new(100, u64::MAX));
+        xs.push(Interval::new(u64::MAX, u64::MAX));
+        xs.sort_unstable();
+        let s = Stabby::new(&xs);
+        assert!(s.len() > 32);
+        assert_eq!(
+            s.stab(u64::MAX),
+            vec![
+                Interval::new(100, u64::MAX),
+                Interval::new(u64::MAX, u64::MAX)
+            ]
+        );
+        assert_eq!(s.stab(u64::MAX - 1), vec![Interval::new(100, u64::MAX)]);
+        assert_eq!(
+            s.stab(102),
+            vec![Interval::new(100, 105), Interval::new(100, u64::MAX)]
+        );
+        let q = Interval::new(u64::MAX - 5, u64::MAX);
+        assert_eq!(s.stab_interval(&q), s.stab(u64::MAX));
+        let v: Stabby<Vec<u64>> = Stabby::with_domain(&xs);
+        let e: Stabby<EytzingerDomain> = Stabby::with_domain(&xs);
+        for q in [0, 102, 399, 400, u64::MAX - 1, u64::MAX] {
+            assert_eq!(v.stab(q), s.stab(q));
+            assert_eq!(e.stab(q), s.stab(q));
+        }
+    }
+
+    #[test]
+    fn test_with_domain() {
+        let mut rng = MiniRng::new(29);
+        let mut xs: Vec<Interval> = (0..300)
+            .map(|_| {
+                let first = rng.rnd() % 2000;
+                Interval::new(first, first + rng.rnd() % 80)
+            })
+            .collect();
+        xs.sort_unstable();
+        xs.dedup();
+        let s = Stabby::new(&xs);
+        let e: Stabby<EytzingerDomain> = Stabby::with_domain(&xs);
+        let i: Stabby<IdentityDomain> = Stabby::with_domain(&xs);
+        let v: Stabby<Vec<u64>> = Stabby::with_domain(&xs);
+        for q in 0..2100 {
+            let expected = s.stab(q);
+            assert_eq!(e.stab(q), expected);
+            assert_eq!(i.stab(q), expected);
+            assert_eq!(v.stab(q), expected);
+            let qi = Interval::new(q, q + 9);
+            let expected = s.stab_interval(&qi);
+            assert_eq!(e.stab_interval(&qi), expected);
+            assert_eq!(i.stab_interval(&qi), expected);
+        }
+    }
+
+    /// A domain that counts its ranks in a `Cell`, so is not `Sync`.
+    #[cfg(not(feature = "rayon"))]
+    struct CountingDomain(Vec<u64>, core::cell::Cell<usize>);
+
+    #[cfg(not(feature = "rayon"))]
+    impl DomainMap for CountingDomain {
+        fn count(&self) -> usize {
+            self.0.count()
+        }
+
+        fn rank(&self, x: u64) -> usize {
+            self.1.set(self.1.get() + 1);
+            self.0.rank(x)
+        }
+
+        fn select(&self, i: usize) -> u64 {
+            self.0.select(i)
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    impl FromPoints for CountingDomain {
+        fn from_points(points: Vec<u64>) -> Self {
+            CountingDomain(points, core::cell::Cell::new(0))
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "rayon"))]
+    fn test_unsync_domain() {
+        let xs: Vec<Interval> = (0..40)
+            .map(|i| Interval::new(i * 10, i * 10 + 15))
+            .collect();
+        let s: Stabby<CountingDomain> = Stabby::with_domain(&xs);
+        assert_eq!(s.stab(12), Stabby::new(&xs).stab(12));
+    }
+}
@@ -0,0 +1,613 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+
+use crate::{collections::Set, Interval, Stabby};
+
+/// The number of intervals held in the unindexed buffer before it is
+/// folded into the levels.
+const BUFFER_CAPACITY: usize = 64;
+
+/// The largest merge carried out by the update that starts it, rather
+/// than in the background, where starting a thread would cost more than
+/// the merge itself.
+const INLINE_MERGE_MAX: usize = 16 * BUFFER_CAPACITY;
+
+/// An immutable level of a [`DynamicStabby`], and the intervals since
+/// removed from it.
+struct Level {
+    index: Arc<Stabby>,
+    removed: Arc<Set<Interval>>,
+}
+
+impl Level {
+    fn new(index: Stabby, removed: Set<Interval>) -> Level {
+        Level {
+            index: Arc::new(index),
+            removed: Arc::new(removed),
+        }
+    }
+
+    fn live(&self) -> usize {
+        self.index.len() - self.removed.len()
+    }
+
+    fn contains(&self, x: &Interval) -> bool {
+        self.index.contains_interval(x) && !self.removed.contains(x)
+    }
+
+    fn stabs(&self, q: u64, removed: &Set<Interval>) -> bool {
+        if self.removed.is_empty() && removed.is_empty() {
+            self.index.stabs(q)
+        } else {
+            self.index
+                .stab(q)
+                .iter()
+                .any(|x| !self.removed.contains(x) && !removed.contains(x))
+        }
+    }
+}
+
+/// A merge of levels, and perhaps the buffer, into a new level, which runs
+/// on a thread of its own.
+#[cfg(feature = "std")]
+struct Job(std::thread::JoinHandle<Stabby>);
+
+#[cfg(feature = "std")]
+impl Job {
+    fn spawn<F: FnOnce() -> Stabby + Send + 'static>(f: F) -> Job {
+        Job(std::thread::spawn(f))
+    }
+
+    fn is_finished(&self) -> bool {
+        #[cfg(test)]
+        if tests::HOLD_MERGES.with(|hold| hold.get()) {
+            return false;
+        }
+        self.0.is_finished()
+    }
+
+    fn join(self) -> Stabby {
+        self.0
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
+
+/// A merge of levels, and perhaps the buffer, into a new level. Without
+/// threads, it is carried out as soon as it is started.
+#[cfg(not(feature = "std"))]
+struct Job(Stabby);
+
+#[cfg(not(feature = "std"))]
+impl Job {
+    fn spawn<F: FnOnce() -> Stabby + Send + 'static>(f: F) -> Job {
+        Job(f())
+    }
+
+    fn is_finished(&self) -> bool {
+        true
+    }
+
+    fn join(self) -> Stabby {
+        self.0
+    }
+}
+
+/// Levels being merged into a new one. Until the merge completes, its
+/// inputs continue to answer queries.
+struct Merge {
+    inputs: Vec<Level>,
+    carry: Vec<Interval>,
+    /// Intervals removed from the inputs or the carry since the merge
+    /// began, which become the tombstones of the new level.
+    removed: Set<Interval>,
+    job: Job,
+}
+
+impl Merge {
+    fn contains(&self, x: &Interval) -> bool {
+        !self.removed.contains(x)
+            && (self.carry.binary_search(x).is_ok() || self.inputs.iter().any(|l| l.contains(x)))
+    }
+
+    fn finish(self) -> Level {
+        Level::new(self.job.join(), self.removed)
+    }
+}
+
+enum Slot {
+    Empty,
+    Ready(Level),
+    Merging(Box<Merge>),
+}
+
+/// Merge two sorted lists of distinct intervals.
+pub(crate) fn merge(xs: Vec<Interval>, ys: Vec<Interval>) -> Vec<Interval> {
+    let mut res: Vec<Interval> = Vec::with_capacity(xs.len() + ys.len());
+    let mut xs = xs.into_iter().peekable();
+    let mut ys = ys.into_iter().peekable();
+    while let (Some(x), Some(y)) = (xs.peek(), ys.peek()) {
+        if x < y {
+            res.push(xs.next().unwrap());
+        } else {
+            res.push(ys.next().unwrap());
+        }
+    }
+    res.extend(xs);
+    res.extend(ys);
+    res
+}
+
+/// Build the level holding the live intervals of some levels and the
+/// buffered intervals carried with them.
+fn build(inputs: Vec<(Arc<Stabby>, Arc<Set<Interval>>)>, carry: Vec<Interval>) -> Stabby {
+    let mut all = carry;
+    for (index, removed) in inputs {
+        let mut live = index.intervals();
+        if !removed.is_empty() {
+            live.retain(|x| !removed.contains(x));
+        }
+        all = merge(live, all);
+    }
+    Stabby::new(&all)
+}
+
+/// A set of intervals supporting insertion and removal as well as the
+/// queries of [`Stabby`].
+///
+/// New intervals are held in a small sorted buffer. When the buffer fills,
+/// it is folded into a series of immutable [`Stabby`] levels whose sizes
+/// grow geometrically, merging with (and emptying) each full level it meets
+/// on the way, in the manner of a binary counter. Each interval is thus
+/// rebuilt into a new level O(log n) times over its lifetime, and a query
+/// consults the buffer and O(log n) levels.
+///
+/// With the `std` feature, all but the smallest merges run on a thread of
+/// their own, and the levels being merged go on answering queries until
+/// the merged level replaces them, at the first update after it is built.
+/// An update waits for a merge only when it must itself merge into the
+/// level being built, having filled every level below it in the meantime.
+/// Without `std`, merges are carried out by the update that starts them,
+/// so the cost per update is amortized O(log n) rebuilds, but a single
+/// update may rebuild the whole set.
+///
+/// Removing an interval held in a level records a tombstone, which hides it
+/// from queries until the level is next merged. A level whose tombstones
+/// come to outnumber its live intervals is rebuilt on its own.
+///
+/// Like [`Stabby`], a `DynamicStabby` holds a set: inserting an interval
+/// that is already present has no effect.
+///
+#[derive(Default)]
+pub struct DynamicStabby {
+    buffer: Vec<Interval>,
+    levels: Vec<Slot>,
+    len: usize,
+}
+
+impl DynamicStabby {
+    /// Create a new, empty, set of intervals.
+    ///
+    pub fn new() -> DynamicStabby {
+        DynamicStabby::default()
+    }
+
+    /// The number of intervals in the set.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Test whether the set is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Test whether the set contains the given interval.
+    ///
+    pub fn contains(&self, x: &Interval) -> bool {
+        self.buffer.binary_search(x).is_ok()
+            || self.levels.iter().any(|slot| match slot {
+                Slot::Empty => false,
+                Slot::Ready(level) => level.contains(x),
+                Slot::Merging(m) => m.contains(x),
+            })
+    }
+
+    /// Add an interval to the set, returning `true` if it was not
+    /// already present.
+    ///
+    pub fn insert(&mut self, x: Interval) -> bool {
+        self.install_finished();
+        if self.contains(&x) {
+            return false;
+        }
+        // An interval removed from a level and inserted again is held
+        // anew, leaving its tombstone in place.
+        let i = self.buffer.binary_search(&x).unwrap_err();
+        self.buffer.insert(i, x);
+        self.len += 1;
+        if self.buffer.len() >= BUFFER_CAPACITY {
+            self.flush();
+        }
+        true
+    }
+
+    /// Remove an interval from the set, returning `true` if it was present.
+    ///
+    pub fn remove(&mut self, x: &Interval) -> bool {
+        self.install_finished();
+        if let Ok(i) = self.buffer.binary_search(x) {
+            self.buffer.remove(i);
+            self.len -= 1;
+            return true;
+        }
+        for i in 0..self.levels.len() {
+            match &mut self.levels[i] {
+                Slot::Empty => {}
+                Slot::Ready(level) => {
+                    if level.contains(x) {
+                        // Levels being merged share their tombstones with
+                        // the merge, but ready levels are never shared.
+                        Arc::make_mut(&mut level.removed).insert(*x);
+                        self.len -= 1;
+                        self.tidy(i);
+                        return true;
+                    }
+                }
+                Slot::Merging(m) => {
+                    if m.contains(x) {
+                        m.removed.insert(*x);
+                        self.len -= 1;
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Wait for any merges running in the background to complete, and put
+    /// the levels they build in place.
+    ///
+    pub fn wait_for_merges(&mut self) {
+        for i in 0..self.levels.len() {
+            self.install(i);
+        }
+    }
+
+    /// Put in place the levels built by merges that have completed.
+    fn install_finished(&mut self) {
+        for i in 0..self.levels.len() {
+            if matches!(&self.levels[i], Slot::Merging(m) if m.job.is_finished()) {
+                self.install(i);
+            }
+        }
+    }
+
+    /// Put in place the level being built in a slot, waiting for it if
+    /// need be.
+    fn install(&mut self, i: usize) {
+        match core::mem::replace(&mut self.levels[i], Slot::Empty) {
+            Slot::Merging(m) => {
+                self.levels[i] = Slot::Ready(m.finish());
+                self.tidy(i);
+            }
+            slot => self.levels[i] = slot,
+        }
+    }
+
+    /// Rebuild a level on its own if it has come to hold more tombstones
+    /// than live intervals, or drop it if it holds none.
+    fn tidy(&mut self, i: usize) {
+        if !matches!(&self.levels[i], Slot::Ready(level) if level.removed.len() > level.live()) {
+            return;
+        }
+        if let Slot::Ready(level) = core::mem::replace(&mut self.levels[i], Slot::Empty) {
+            if level.live() > 0 {
+                self.levels[i] = self.start_merge(Vec::from([level]), Vec::new());
+            }
+        }
+    }
+
+    /// Start merging levels and buffered intervals into a new level.
+    fn start_merge(&self, inputs: Vec<Level>, carry: Vec<Interval>) -> Slot {
+        let size: usize = carry.len() + inputs.iter().map(|l| l.live()).sum::<usize>();
+        let parts: Vec<(Arc<Stabby>, Arc<Set<Interval>>)> = inputs
+            .iter()
+            .map(|l| (l.index.clone(), l.removed.clone()))
+            .collect();
+        if size <= INLINE_MERGE_MAX {
+            return Slot::Ready(Level::new(build(parts, carry), Set::new()));
+        }
+        let c = carry.clone();
+        Slot::Merging(Box::new(Merge {
+            inputs,
+            carry,
+            removed: Set::new(),
+            job: Job::spawn(move || build(parts, c)),
+        }))
+    }
+
+    /// Fold the buffer into the levels.
+    fn flush(&mut self) {
+        let carry = core::mem::take(&mut self.buffer);
+        let mut inputs: Vec<Level> = Vec::new();
+        for i in 0.. {
+            if i == self.levels.len() {
+                self.levels.push(Slot::Empty);
+            }
+            match core::mem::replace(&mut self.levels[i], Slot::Empty) {
+                Slot::Ready(level) => inputs.push(level),
+                // A level still being built, perhaps by a rebuild that
+                // began when it was installed, is waited for and carried.
+                Slot::Merging(m) => inputs.push(m.finish()),
+                Slot::Empty => {
+                    self.levels[i] = self.start_merge(inputs, carry);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Gather the matching intervals from the buffer and from each level.
+    fn collect(
+        &self,
+        in_buffer: impl Fn(&Interval) -> bool,
+        from_level: impl Fn(&Stabby) -> Vec<Interval>,
+    ) -> Vec<Interval> {
+        let mut res: Vec<Interval> = self
+            .buffer
+            .iter()
+            .filter(|x| in_buffer(x))
+            .copied()
+            .collect();
+        let mut gather = |level: &Level, removed: &Set<Interval>| {
+            res.extend(
+                from_level(&level.index)
+                    .into_iter()
+                    .filter(|x| !level.removed.contains(x) && !removed.contains(x)),
+            );
+        };
+        let none: Set<Interval> = Set::new();
+        for slot in self.levels.iter() {
+            match slot {
+                Slot::Empty => {}
+                Slot::Ready(level) => gather(level, &none),
+                Slot::Merging(m) => {
+                    for level in m.inputs.iter() {
+                        gather(level, &m.removed);
+                    }
+                }
+            }
+        }
+        for slot in self.levels.iter() {
+            if let Slot::Merging(m) = slot {
+                res.extend(
+                    m.carry
+                        .iter()
+                        .filter(|x| in_buffer(x) && !m.removed.contains(x)),
+                );
+            }
+        }
+        res.sort_unstable();
+        res
+    }
+
+    /// Test if a position stabs any intervals.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        let none: Set<Interval> = Set::new();
+        self.buffer.iter().any(|x| x.contains(q))
+            || self.levels.iter().any(|slot| match slot {
+                Slot::Empty => false,
+                Slot::Ready(level) => level.stabs(q, &none),
+                Slot::Merging(m) => {
+                    m.carry
+                        .iter()
+                        .any(|x| x.contains(q) && !m.removed.contains(x))
+                        || m.inputs.iter().any(|level| level.stabs(q, &m.removed))
+                }
+            })
+    }
+
+    /// Retrieve the list of intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        self.collect(|x| x.contains(q), |s| s.stab(q))
+    }
+
+    /// Find the list of intervals that intersect the given query interval.
+    /// The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        self.collect(|x| x.overlaps(q), |s| s.stab_interval(q))
+    }
+
+    /// Recover the intervals in the set, in sorted order.
+    ///
+    pub(crate) fn intervals(&self) -> Vec<Interval> {
+        self.collect(|_| true, |s| s.intervals())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::BTreeSet};
+
+    use super::*;
+    use crate::test_util::MiniRng;
+
+    std::thread_local! {
+        /// Whether merges running in the background are reported as
+        /// unfinished, however far they have got, so that tests can
+        /// update a set while a merge is in flight.
+        pub(super) static HOLD_MERGES: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn check(d: &DynamicStabby, oracle: &BTreeSet<Interval>) {
+        assert_eq!(d.len(), oracle.len());
+        for q in (0..2100).step_by(7) {
+            let expected: Vec<Interval> = oracle
+                .iter()
+                .filter(|x| x.first <= q && q <= x.last)
+                .copied()
+                .collect();
+            assert_eq!(d.stabs(q), !expected.is_empty());
+            assert_eq!(d.stab(q), expected);
+            let qi = Interval::new(q, q + 13);
+            let expected: Vec<Interval> = oracle
+                .iter()
+                .filter(|x| x.first <= qi.last && qi.first <= x.last)
+                .copied()
+                .collect();
+            assert_eq!(d.stab_interval(&qi), expected);
+        }
+    }
+
+    #[test]
+    fn test_insert_only() {
+        let mut d = DynamicStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut rng = MiniRng::new(17);
+        for i in 0..1500 {
+            let first = rng.rnd() % 2000;
+            let x = Interval::new(first, first + rng.rnd() % 50);
+            assert_eq!(d.insert(x), oracle.insert(x));
+            if i % 250 == 0 {
+                check(&d, &oracle);
+            }
+        }
+        check(&d, &oracle);
+        assert!(!d.insert(*oracle.iter().next().unwrap()));
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut d = DynamicStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut rng = MiniRng::new(91);
+        for i in 0..4000 {
+            let first = rng.rnd() % 2000;
+            let x = Interval::new(first, first + rng.rnd() % 50);
+            if rng.rnd().is_multiple_of(3) {
+                let y = oracle
+                    .iter()
+                    .nth((rng.rnd() as usize) % (oracle.len() + 1))
+                    .copied();
+                if let Some(y) = y {
+                    assert!(d.remove(&y));
+                    oracle.remove(&y);
+                    assert!(!d.contains(&y));
+                    assert!(!d.remove(&y));
+                }
+            } else {
+                assert_eq!(d.insert(x), oracle.insert(x));
+                assert!(d.contains(&x));
+            }
+            if i % 500 == 0 {
+                check(&d, &oracle);
+            }
+        }
+        check(&d, &oracle);
+
+        let all: Vec<Interval> = oracle.iter().copied().collect();
+        for x in all.iter() {
+            assert!(d.remove(x));
+            oracle.remove(x);
+        }
+        assert!(d.is_empty());
+        check(&d, &oracle);
+    }
+
+    #[test]
+    fn test_background_merges() {
+        let mut d = DynamicStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut rng = MiniRng::new(5);
+        while oracle.len() < 20 * INLINE_MERGE_MAX {
+            let first = rng.rnd() % 100_000;
+            let x = Interval::new(first, first + rng.rnd() % 50);
+            assert_eq!(d.insert(x), oracle.insert(x));
+            if oracle.len().is_multiple_of(1000) {
+                // Merges may still be running, so their inputs answer.
+                let q = Interval::new(first, first + 500);
+                let expected: Vec<Interval> =
+                    oracle.iter().filter(|y| y.overlaps(&q)).copied().collect();
+                assert_eq!(d.stab_interval(&q), expected);
+            }
+        }
+        d.wait_for_merges();
+        assert!(d
+            .levels
+            .iter()
+            .all(|slot| !matches!(slot, Slot::Merging(_))));
+        let sizes: usize = d
+            .levels
+            .iter()
+            .map(|slot| match slot {
+                Slot::Ready(level) => level.live(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(sizes + d.buffer.len(), oracle.len());
+
+        // Removing most intervals rebuilds the levels that held them.
+        let all: Vec<Interval> = oracle.iter().copied().collect();
+        for x in all.iter().step_by(4) {
+            assert!(!d.insert(*x) && d.remove(x));
+            oracle.remove(x);
+        }
+        for x in all.iter().skip(1).step_by(4) {
+            assert!(d.remove(x));
+            oracle.remove(x);
+        }
+        for x in all.iter().skip(2).step_by(4) {
+            assert!(d.remove(x));
+            oracle.remove(x);
+        }
+        d.wait_for_merges();
+        for slot in d.levels.iter() {
+            if let Slot::Ready(level) = slot {
+                assert!(level.removed.len() <= level.live());
+            }
+        }
+        assert_eq!(d.len(), oracle.len());
+        assert_eq!(
+            d.stab_interval(&Interval::new(0, u64::MAX)),
+            oracle.iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_flush_into_rebuilt_level() {
+        HOLD_MERGES.with(|hold| hold.set(true));
+        let mut d = DynamicStabby::new();
+        let n = 64 * BUFFER_CAPACITY;
+        for i in 0..n as u64 {
+            d.insert(Interval::new(i * 10, i * 10 + 5));
+        }
+        // All of them are being merged into a single level, and more than
+        // half are removed while the merge runs.
+        assert!(matches!(d.levels.last(), Some(Slot::Merging(_))));
+        let removed = n / 2 + 50;
+        for i in 0..removed as u64 {
+            assert!(d.remove(&Interval::new(i * 10, i * 10 + 5)));
+        }
+        // The next flush to reach that level installs it, which starts a
+        // rebuild, and must carry the level being rebuilt.
+        for i in n as u64..2 * n as u64 {
+            d.insert(Interval::new(i * 10, i * 10 + 5));
+        }
+        HOLD_MERGES.with(|hold| hold.set(false));
+        d.wait_for_merges();
+        let expected: Vec<Interval> = (removed as u64..2 * n as u64)
+            .map(|i| Interval::new(i * 10, i * 10 + 5))
+            .collect();
+        assert_eq!(d.len(), expected.len());
+        assert_eq!(d.stab_interval(&Interval::new(0, u64::MAX)), expected);
+    }
+}
@@ -0,0 +1,135 @@
+use alloc::string::String;
+use core::fmt::Display;
+
+/// The errors that can arise when reading, writing or validating
+/// a stabby index.
+///
+#[derive(Debug)]
+pub enum StabbyError {
+    /// An underlying I/O operation failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+
+    /// The input ended before a complete index could be read.
+    Truncated,
+
+    /// The input does not begin with the stabby magic number.
+    BadMagic,
+
+    /// The index was written with a version of the format
+    /// that this version of the library does not understand.
+    UnsupportedVersion {
+        /// The version recorded in the input.
+        found: u32,
+        /// The newest version this library can read.
+        supported: u32,
+    },
+
+    /// The checksum recorded in the input does not match its contents.
+    ChecksumMismatch {
+        /// The checksum recorded in the input.
+        expected: u64,
+        /// The checksum computed over the contents.
+        found: u64,
+    },
+
+    /// The input is well formed, but fails a consistency check.
+    Corrupt(String),
+
+    /// The same name was given to more than one index in a set.
+    DuplicateName(String),
+
+    /// An interval was appended that starts before an interval
+    /// already in an append-only index.
+    OutOfOrder {
+        /// The greatest start already in the index.
+        previous: u64,
+        /// The start of the interval that was rejected.
+        found: u64,
+    },
+
+    /// A pair of coordinates has no equivalent in another coordinate
+    /// system, such as an empty half-open interval, or a closed interval
+    /// ending at `u64::MAX` given half-open.
+    InvalidCoordinates {
+        /// The start, as given.
+        start: u64,
+        /// The end, as given.
+        end: u64,
+        /// What was wrong with them.
+        reason: &'static str,
+    },
+
+    /// A point is too large for the representation of the domain that
+    /// was chosen, such as [`IdentityDomain`](crate::IdentityDomain).
+    DomainOverflow {
+        /// The largest point of the domain.
+        point: u64,
+    },
+
+    /// Text could not be parsed.
+    Parse {
+        /// The text that was given.
+        input: String,
+        /// What was wrong with it.
+        reason: &'static str,
+    },
+}
+
+impl Display for StabbyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            StabbyError::Io(e) => write!(f, "i/o error: {}", e),
+            StabbyError::Truncated => write!(f, "truncated index"),
+            StabbyError::BadMagic => write!(f, "not a stabby index (bad magic number)"),
+            StabbyError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "unsupported format version {} (newest supported is {})",
+                found, supported
+            ),
+            StabbyError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch: expected {:#018x}, found {:#018x}",
+                expected, found
+            ),
+            StabbyError::Corrupt(msg) => write!(f, "corrupt index: {}", msg),
+            StabbyError::DuplicateName(name) => write!(f, "duplicate index name: {}", name),
+            StabbyError::OutOfOrder { previous, found } => write!(
+                f,
+                "interval starting at {} appended after one starting at {}",
+                found, previous
+            ),
+            StabbyError::InvalidCoordinates { start, end, reason } => {
+                write!(f, "invalid coordinates ({}, {}): {}", start, end, reason)
+            }
+            StabbyError::DomainOverflow { point } => {
+                write!(f, "point {} is too large for the domain", point)
+            }
+            StabbyError::Parse { input, reason } => {
+                write!(f, "cannot parse {:?}: {}", input, reason)
+            }
+        }
+    }
+}
+
+impl core::error::Error for StabbyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            StabbyError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for StabbyError {
+    fn from(value: std::io::Error) -> Self {
+        if value.kind() == std::io::ErrorKind::UnexpectedEof {
+            StabbyError::Truncated
+        } else {
+            StabbyError::Io(value)
+        }
+    }
+}
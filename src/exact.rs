@@ -0,0 +1,100 @@
+use alloc::vec::Vec;
+
+use crate::{
+    dense::{DenseInterval, DenseLookup, DenseStabby},
+    domain::{to_dense, to_sparse, DomainMap},
+    sparse::Repr,
+    Interval, Stabby,
+};
+
+/// The intervals of the dense structure starting at a position, shortest
+/// first.
+fn dense_with_start(dense: &DenseStabby, p: usize) -> Vec<DenseInterval> {
+    match dense.start(p).filter(|a| a.first == p) {
+        None => Vec::new(),
+        Some(a) => dense.smaller(&a).chain([a]).collect(),
+    }
+}
+
+impl<D: DomainMap> Stabby<D> {
+    /// Test if the index holds exactly the given interval.
+    ///
+    pub fn contains_interval(&self, x: &Interval) -> bool {
+        match &self.repr {
+            Repr::Small(xs) => xs.binary_search(x).is_ok(),
+            Repr::Full { domain, dense, .. } => {
+                // Endpoints of intervals are points of the domain, which
+                // map to even positions.
+                let y = DenseInterval::new(to_dense(domain, x.first), to_dense(domain, x.last));
+                if !y.first.is_multiple_of(2)
+                    || !y.last.is_multiple_of(2)
+                    || y.first >= dense.size()
+                {
+                    return false;
+                }
+                dense_with_start(dense, y.first).binary_search(&y).is_ok()
+            }
+        }
+    }
+
+    /// Find the intervals that start at the given position, in sorted
+    /// order.
+    ///
+    pub fn with_start(&self, first: u64) -> Vec<Interval> {
+        match &self.repr {
+            Repr::Small(xs) => {
+                let i = xs.partition_point(|x| x.first < first);
+                xs[i..]
+                    .iter()
+                    .take_while(|x| x.first == first)
+                    .copied()
+                    .collect()
+            }
+            Repr::Full { domain, dense, .. } => {
+                let p = to_dense(domain, first);
+                if !p.is_multiple_of(2) || p >= dense.size() {
+                    return Vec::new();
+                }
+                dense_with_start(dense, p)
+                    .into_iter()
+                    .map(|y| Interval::new(first, to_sparse(domain, y.last)))
+                    .collect()
+            }
+        }
+    }
+
+    /// Find the intervals that end at the given position, in sorted order.
+    ///
+    pub fn with_end(&self, last: u64) -> Vec<Interval> {
+        // Every such interval is stabbed by its end.
+        let mut res = self.stab(last);
+        res.retain(|x| x.last == last);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::many_intervals;
+
+    #[test]
+    fn test_exact_lookups() {
+        for n in [0, 10, 300] {
+            let xs: Vec<Interval> = many_intervals().into_iter().take(n).collect();
+            let s = Stabby::new(&xs);
+            for p in 0..600 {
+                let want: Vec<Interval> = xs.iter().filter(|x| x.first == p).copied().collect();
+                assert_eq!(s.with_start(p), want);
+                let want: Vec<Interval> = xs.iter().filter(|x| x.last == p).copied().collect();
+                assert_eq!(s.with_end(p), want);
+                for len in [0, 5, 10, 30] {
+                    let x = Interval::new(p, p + len);
+                    assert_eq!(s.contains_interval(&x), xs.contains(&x));
+                }
+            }
+            assert!(!s.contains_interval(&Interval::new(0, u64::MAX)));
+            assert!(s.with_start(u64::MAX).is_empty());
+        }
+    }
+}
@@ -0,0 +1,1029 @@
+//! The binary file format used by [`Stabby::write_to`](crate::Stabby::write_to)
+//! and [`Stabby::read_from`](crate::Stabby::read_from).
+//!
+//! A serialized index records the fully built data structure, so loading it
+//! involves no sorting, ranking or sweeping of the intervals. Every integer
+//! is stored little-endian, so files may be moved freely between machines.
+//!
+//! # Layout
+//!
+//! A file consists of a fixed 96 byte header followed by the payload.
+//! Every header field is a little-endian `u64` unless noted otherwise.
+//!
+//! | offset | field          | notes                                              |
+//! |-------:|----------------|----------------------------------------------------|
+//! |      0 | magic          | the 8 bytes [`MAGIC`]                              |
+//! |      8 | version        | `u32`, [`FORMAT_VERSION`] for files written now    |
+//! |     12 | flags          | `u32`, reserved, always 0                          |
+//! |     16 | checksum       | FNV-1a over bytes 24 to the end of the payload     |
+//! |     24 | fingerprint    | FNV-1a over the stored intervals (see below)       |
+//! |     32 | domain length  | the number `k` of distinct endpoints, including 0  |
+//! |     40 | interval count | the number of intervals in the index               |
+//! |     48 | dense length   | the size `d` of the dense domain, always `2k`      |
+//! |     56 | parent count   | entries in the `parent` table                      |
+//! |     64 | left count     | entries in the `left` table                        |
+//! |     72 | last count     | entries in the `last` table                        |
+//! |     80 | smaller count  | entries in the `smaller` table                     |
+//! |     88 | pool count     | intervals in the `smaller` pool                    |
+//!
+//! The payload follows immediately, as a sequence of little-endian `u64`
+//! words, in these sections:
+//!
+//! 1. the domain: the `k` sorted endpoints;
+//! 2. `start`: `d` dense intervals, indexed by dense position;
+//! 3. `start2`: `d` dense intervals, indexed by dense position;
+//! 4. `parent`, `left` and `last`: tables of (key, value) pairs of dense
+//!    intervals, each sorted by key;
+//! 5. `smaller`: a table of (key, offset, length) records, sorted by key,
+//!    each naming a run of the pool;
+//! 6. the pool: the dense intervals referenced by `smaller`.
+//!
+//! A dense interval occupies two words, `first` then `last`. An empty slot
+//! in `start` or `start2` is stored as `u64::MAX` in both words.
+//!
+//! The fingerprint depends only on the intervals held in the index: it is
+//! FNV-1a over the `first` and `last` of each interval, as little-endian
+//! `u64`s, in sorted order. Two indexes over the same intervals therefore
+//! have the same fingerprint, whichever version of the format holds them.
+//!
+//! # Index sets
+//!
+//! Several named indexes, for example one per contig, may be stored in a
+//! single file with [`write_indexes`]. Such a file begins with a 24 byte
+//! header:
+//!
+//! | offset | field   | notes                                         |
+//! |-------:|---------|-----------------------------------------------|
+//! |      0 | magic   | the 8 bytes [`SET_MAGIC`]                     |
+//! |      8 | version | `u32`, [`FORMAT_VERSION`] for files written now |
+//! |     12 | flags   | `u32`, reserved, always 0                     |
+//! |     16 | count   | the number of indexes in the set              |
+//!
+//! followed by one entry per index, consisting of three `u64`s (the length
+//! of the name in bytes, and the offset from the start of the file and the
+//! length of the index) and then the UTF-8 name, padded with zero bytes to
+//! a multiple of 8. The indexes follow the entries, each a complete index
+//! in the format above, starting on an 8 byte boundary. Entries are stored
+//! in sorted order of name, and names are unique.
+//!
+//! Because each entry records where its index lies, a reader can load or
+//! map just the indexes it needs.
+//!
+
+use std::{
+    io::{Read, Write},
+    ops::Range,
+};
+
+use crate::{
+    collections::Map,
+    dense::{DenseInterval, DenseLookup, DenseStabby},
+    domain::{Domain, DomainMap, FromPoints},
+    sparse::{to_sparse_intervals, SMALL_MAX},
+    Interval, Stabby, StabbyError,
+};
+
+/// The magic number at the start of every serialized index.
+pub const MAGIC: [u8; 8] = *b"STABBYIX";
+
+/// The version of the format written by this version of the library.
+/// Files with a larger version number are rejected when read.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The length in bytes of the fixed header.
+pub const HEADER_LEN: usize = 96;
+
+/// The magic number at the start of a file holding a set of named indexes.
+pub const SET_MAGIC: [u8; 8] = *b"STABBYMX";
+
+const SET_HEADER_LEN: usize = 24;
+
+const CHECKSUM_START: usize = 24;
+const NONE: u64 = u64::MAX;
+
+/// A 64 bit FNV-1a hasher, used for both the checksum and the fingerprint.
+///
+pub(crate) struct Fnv64(u64);
+
+impl Fnv64 {
+    pub(crate) fn new() -> Fnv64 {
+        Fnv64(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for b in bytes.iter() {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Compute the fingerprint of a sorted sequence of intervals.
+///
+pub(crate) fn fingerprint<I: Iterator<Item = Interval>>(xs: I) -> u64 {
+    let mut h = Fnv64::new();
+    for x in xs {
+        h.update(&x.first.to_le_bytes());
+        h.update(&x.last.to_le_bytes());
+    }
+    h.finish()
+}
+
+fn corrupt(msg: &str) -> StabbyError {
+    StabbyError::Corrupt(msg.to_string())
+}
+
+fn get_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut w = [0u8; 8];
+    w.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(w)
+}
+
+fn get_usize(bytes: &[u8], offset: usize) -> Result<usize, StabbyError> {
+    usize::try_from(get_u64(bytes, offset)).map_err(|_| corrupt("length out of range"))
+}
+
+fn put_interval(buf: &mut Vec<u8>, x: &DenseInterval) {
+    buf.extend_from_slice(&(x.first as u64).to_le_bytes());
+    buf.extend_from_slice(&(x.last as u64).to_le_bytes());
+}
+
+fn put_option(buf: &mut Vec<u8>, x: &Option<DenseInterval>) {
+    match x {
+        None => {
+            buf.extend_from_slice(&NONE.to_le_bytes());
+            buf.extend_from_slice(&NONE.to_le_bytes());
+        }
+        Some(x) => put_interval(buf, x),
+    }
+}
+
+/// The decoded fixed-size header of a serialized index.
+///
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Header {
+    pub(crate) checksum: u64,
+    pub(crate) fingerprint: u64,
+    pub(crate) domain_len: usize,
+    pub(crate) interval_count: usize,
+    pub(crate) dense_len: usize,
+    pub(crate) parent_len: usize,
+    pub(crate) left_len: usize,
+    pub(crate) last_len: usize,
+    pub(crate) smaller_len: usize,
+    pub(crate) pool_len: usize,
+}
+
+impl Header {
+    /// Decode a header, checking the magic number and version.
+    ///
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Header, StabbyError> {
+        if bytes.len() < MAGIC.len() {
+            return Err(StabbyError::Truncated);
+        }
+        if bytes[0..MAGIC.len()] != MAGIC {
+            return Err(StabbyError::BadMagic);
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(StabbyError::Truncated);
+        }
+        let version = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+        if version > FORMAT_VERSION {
+            return Err(StabbyError::UnsupportedVersion {
+                found: version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        if version == 0 {
+            return Err(corrupt("format version 0"));
+        }
+        let flags = u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+        if flags != 0 {
+            return Err(corrupt("unknown flags"));
+        }
+        Ok(Header {
+            checksum: get_u64(bytes, 16),
+            fingerprint: get_u64(bytes, 24),
+            domain_len: get_usize(bytes, 32)?,
+            interval_count: get_usize(bytes, 40)?,
+            dense_len: get_usize(bytes, 48)?,
+            parent_len: get_usize(bytes, 56)?,
+            left_len: get_usize(bytes, 64)?,
+            last_len: get_usize(bytes, 72)?,
+            smaller_len: get_usize(bytes, 80)?,
+            pool_len: get_usize(bytes, 88)?,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+        buf.extend_from_slice(&MAGIC);
+        buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        for x in [
+            self.checksum,
+            self.fingerprint,
+            self.domain_len as u64,
+            self.interval_count as u64,
+            self.dense_len as u64,
+            self.parent_len as u64,
+            self.left_len as u64,
+            self.last_len as u64,
+            self.smaller_len as u64,
+            self.pool_len as u64,
+        ] {
+            buf.extend_from_slice(&x.to_le_bytes());
+        }
+        buf
+    }
+
+    /// The number of words in the payload described by this header.
+    ///
+    fn payload_words(&self) -> Option<usize> {
+        let tables = self
+            .parent_len
+            .checked_add(self.left_len)?
+            .checked_add(self.last_len)?
+            .checked_add(self.smaller_len)?
+            .checked_mul(4)?;
+        self.domain_len
+            .checked_add(self.dense_len.checked_mul(4)?)?
+            .checked_add(tables)?
+            .checked_add(self.pool_len.checked_mul(2)?)
+    }
+
+    /// The number of bytes in the payload described by this header.
+    ///
+    pub(crate) fn payload_len(&self) -> Result<usize, StabbyError> {
+        self.payload_words()
+            .and_then(|w| w.checked_mul(8))
+            .ok_or_else(|| corrupt("section lengths overflow"))
+    }
+}
+
+/// The position of each section of a serialized index, in words from
+/// the start of its payload.
+///
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Layout {
+    pub(crate) header: Header,
+    start: usize,
+    start2: usize,
+    parent: usize,
+    left: usize,
+    last: usize,
+    smaller: usize,
+    pool: usize,
+}
+
+impl Layout {
+    fn new(header: Header) -> Layout {
+        // These cannot overflow once payload_len has succeeded.
+        let start = header.domain_len;
+        let start2 = start + 2 * header.dense_len;
+        let parent = start2 + 2 * header.dense_len;
+        let left = parent + 4 * header.parent_len;
+        let last = left + 4 * header.left_len;
+        let smaller = last + 4 * header.last_len;
+        let pool = smaller + 4 * header.smaller_len;
+        Layout {
+            header,
+            start,
+            start2,
+            parent,
+            left,
+            last,
+            smaller,
+            pool,
+        }
+    }
+}
+
+/// A view of a serialized index, answering lookups directly from its bytes.
+///
+pub(crate) struct RawIndex<'a> {
+    layout: Layout,
+    bytes: &'a [u8],
+    payload: &'a [u8],
+}
+
+impl<'a> RawIndex<'a> {
+    /// Decode and validate a complete serialized index.
+    ///
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<RawIndex<'a>, StabbyError> {
+        let raw = RawIndex::open(bytes)?;
+        raw.verify()?;
+        Ok(raw)
+    }
+
+    /// Decode the header of a serialized index, and check that it agrees
+    /// with itself and with the length of the bytes, without reading the
+    /// payload. Queries on an index opened this way may panic or give
+    /// wrong answers if the payload is damaged, until it is verified.
+    ///
+    pub(crate) fn open(bytes: &'a [u8]) -> Result<RawIndex<'a>, StabbyError> {
+        let header = Header::parse(bytes)?;
+        let len = header.payload_len()?;
+        if bytes.len() - HEADER_LEN < len {
+            return Err(StabbyError::Truncated);
+        }
+        if bytes.len() - HEADER_LEN > len {
+            return Err(corrupt("trailing bytes after payload"));
+        }
+        if header.domain_len == 0 {
+            return Err(corrupt("domain must begin with 0"));
+        }
+        if header.dense_len != 2 * header.domain_len {
+            return Err(corrupt("dense length does not match the domain"));
+        }
+        if header.interval_count != header.parent_len + header.pool_len {
+            return Err(corrupt("interval count does not match the tables"));
+        }
+        Ok(RawIndex::with_layout(Layout::new(header), bytes))
+    }
+
+    /// Check the checksum of the index, and the structure of its payload.
+    /// This reads every byte of the index.
+    ///
+    pub(crate) fn verify(&self) -> Result<(), StabbyError> {
+        let mut h = Fnv64::new();
+        h.update(&self.bytes[CHECKSUM_START..]);
+        let checksum = self.layout.header.checksum;
+        if h.finish() != checksum {
+            return Err(StabbyError::ChecksumMismatch {
+                expected: checksum,
+                found: h.finish(),
+            });
+        }
+        self.validate()
+    }
+
+    /// Make a view over bytes whose header has previously been checked by
+    /// [`RawIndex::open`], reusing the layout it computed.
+    ///
+    pub(crate) fn with_layout(layout: Layout, bytes: &'a [u8]) -> RawIndex<'a> {
+        RawIndex {
+            layout,
+            bytes,
+            payload: &bytes[HEADER_LEN..],
+        }
+    }
+
+    pub(crate) fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Check that the intervals in the index match the fingerprint recorded
+    /// in its header.
+    ///
+    pub(crate) fn check_fingerprint(&self) -> Result<(), StabbyError> {
+        let xs = to_sparse_intervals(self, &self.intervals());
+        if fingerprint(xs.into_iter()) != self.layout.header.fingerprint {
+            return Err(corrupt("fingerprint does not match the intervals"));
+        }
+        Ok(())
+    }
+
+    fn word(&self, i: usize) -> u64 {
+        get_u64(self.payload, 8 * i)
+    }
+
+    /// Decode the dense interval at word `i`. Only valid after validation.
+    fn interval(&self, i: usize) -> DenseInterval {
+        DenseInterval::new(self.word(i) as usize, self.word(i + 1) as usize)
+    }
+
+    fn option(&self, i: usize) -> Option<DenseInterval> {
+        if self.word(i) == NONE {
+            None
+        } else {
+            Some(self.interval(i))
+        }
+    }
+
+    fn domain(&self, i: usize) -> u64 {
+        self.word(i)
+    }
+
+    fn find(&self, base: usize, len: usize, key: &DenseInterval) -> Option<usize> {
+        let mut first = 0;
+        let mut count = len;
+        while count > 0 {
+            let step = count / 2;
+            let i = first + step;
+            if self.interval(base + 4 * i) < *key {
+                first = i + 1;
+                count -= step + 1;
+            } else {
+                count = step;
+            }
+        }
+        if first < len && self.interval(base + 4 * first) == *key {
+            Some(base + 4 * first)
+        } else {
+            None
+        }
+    }
+
+    fn lookup(&self, base: usize, len: usize, key: &DenseInterval) -> Option<DenseInterval> {
+        self.find(base, len, key).map(|i| self.interval(i + 2))
+    }
+
+    /// Find the run of the pool holding the intervals shadowed by `key`,
+    /// as a range of pool indexes.
+    fn smaller_run(&self, key: &DenseInterval) -> Option<Range<usize>> {
+        self.find(self.layout.smaller, self.layout.header.smaller_len, key)
+            .map(|i| {
+                let offset = self.word(i + 2) as usize;
+                offset..offset + self.word(i + 3) as usize
+            })
+    }
+
+    fn pool(&self, i: usize) -> DenseInterval {
+        self.interval(self.layout.pool + 2 * i)
+    }
+
+    fn table(&self, base: usize, len: usize) -> Map<DenseInterval, DenseInterval> {
+        (0..len)
+            .map(|i| (self.interval(base + 4 * i), self.interval(base + 4 * i + 2)))
+            .collect()
+    }
+
+    /// Check a dense interval stored at word `i` lies within the dense domain.
+    fn check_interval(&self, i: usize) -> Result<DenseInterval, StabbyError> {
+        let (f, l) = (self.word(i), self.word(i + 1));
+        let d = self.layout.header.dense_len as u64;
+        if f > l || l >= d || f % 2 != 0 || l % 2 != 0 {
+            return Err(corrupt("dense interval out of range"));
+        }
+        Ok(self.interval(i))
+    }
+
+    fn check_option(&self, i: usize) -> Result<Option<DenseInterval>, StabbyError> {
+        if self.word(i) == NONE && self.word(i + 1) == NONE {
+            Ok(None)
+        } else {
+            self.check_interval(i).map(Some)
+        }
+    }
+
+    /// Check the keys of a table are strictly increasing, so that binary
+    /// search works, and hand each (key, value) pair to `f`.
+    fn check_table<F>(&self, base: usize, len: usize, mut f: F) -> Result<(), StabbyError>
+    where
+        F: FnMut(DenseInterval, usize) -> Result<(), StabbyError>,
+    {
+        let mut prev: Option<DenseInterval> = None;
+        for i in 0..len {
+            let k = self.check_interval(base + 4 * i)?;
+            if let Some(p) = prev {
+                if p >= k {
+                    return Err(corrupt("table keys are not sorted"));
+                }
+            }
+            prev = Some(k);
+            f(k, base + 4 * i + 2)?;
+        }
+        Ok(())
+    }
+
+    /// Check the structural invariants that the query algorithms rely on,
+    /// so that a damaged index fails here rather than misbehaving later.
+    ///
+    fn validate(&self) -> Result<(), StabbyError> {
+        // The lengths of the sections have already been checked by open.
+        let h = &self.layout.header;
+        if self.domain(0) != 0 {
+            return Err(corrupt("domain must begin with 0"));
+        }
+        for i in 1..h.domain_len {
+            if self.domain(i - 1) >= self.domain(i) {
+                return Err(corrupt("domain is not strictly increasing"));
+            }
+        }
+        for q in 0..h.dense_len {
+            if let Some(a) = self.check_option(self.layout.start + 2 * q)? {
+                if a.first > q || a.last < q {
+                    return Err(corrupt("start interval does not contain its position"));
+                }
+            }
+            if let Some(a) = self.check_option(self.layout.start2 + 2 * q)? {
+                if a.first > q {
+                    return Err(corrupt("start2 interval begins after its position"));
+                }
+            }
+        }
+
+        // Parents always begin before their children, left siblings begin
+        // and end before their right siblings, and last children lie within
+        // their parents, beginning after them. So every step of a walk up
+        // the parents, back through start2, or along left and last moves
+        // strictly downwards in some order, and the walk terminates.
+        let zero = DenseInterval::zero();
+        self.check_table(self.layout.parent, h.parent_len, |k, i| {
+            let v = self.check_interval(i)?;
+            if v != zero && v.first >= k.first {
+                return Err(corrupt("parent does not precede child"));
+            }
+            Ok(())
+        })?;
+        self.check_table(self.layout.left, h.left_len, |k, i| {
+            let v = self.check_interval(i)?;
+            if v.first >= k.first || v.last >= k.last {
+                return Err(corrupt("left sibling does not precede interval"));
+            }
+            Ok(())
+        })?;
+        self.check_table(self.layout.last, h.last_len, |k, i| {
+            let v = self.check_interval(i)?;
+            if k != zero && (v.first <= k.first || v.last > k.last) {
+                return Err(corrupt("last child does not lie within parent"));
+            }
+            Ok(())
+        })?;
+        self.check_table(self.layout.smaller, h.smaller_len, |_k, i| {
+            let end = (self.word(i) as usize).checked_add(self.word(i + 1) as usize);
+            match end {
+                Some(e) if e <= h.pool_len => Ok(()),
+                _ => Err(corrupt("smaller run lies outside the pool")),
+            }
+        })?;
+        for i in 0..h.pool_len {
+            self.check_interval(self.layout.pool + 2 * i)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the in-memory data structure, in the small form if the
+    /// index holds few enough intervals.
+    ///
+    pub(crate) fn to_stabby(&self) -> Stabby {
+        let h = &self.layout.header;
+        if h.interval_count <= SMALL_MAX {
+            return Stabby::small(to_sparse_intervals(self, &self.intervals()));
+        }
+        let domain: Vec<u64> = (0..h.domain_len).map(|i| self.domain(i)).collect();
+        let mut smaller: Map<DenseInterval, Vec<DenseInterval>> = Map::new();
+        for i in 0..h.smaller_len {
+            let k = self.interval(self.layout.smaller + 4 * i);
+            if let Some(r) = self.smaller_run(&k) {
+                smaller.insert(k, r.map(|j| self.pool(j)).collect());
+            }
+        }
+        let dense = DenseStabby {
+            smaller,
+            start: (0..h.dense_len).map(|q| self.start(q)).collect(),
+            start2: (0..h.dense_len).map(|q| self.start2(q)).collect(),
+            parent: self.table(self.layout.parent, h.parent_len),
+            last: self.table(self.layout.last, h.last_len),
+            left: self.table(self.layout.left, h.left_len),
+        };
+        Stabby::from_layers(Domain::from_points(domain), dense)
+    }
+}
+
+impl DomainMap for RawIndex<'_> {
+    fn count(&self) -> usize {
+        self.layout.header.domain_len
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        let mut first = 0;
+        let mut count = self.layout.header.domain_len;
+        while count > 0 {
+            let step = count / 2;
+            let i = first + step;
+            if self.domain(i) < x {
+                first = i + 1;
+                count -= step + 1;
+            } else {
+                count = step;
+            }
+        }
+        first
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        self.domain(i)
+    }
+}
+
+impl DenseLookup for RawIndex<'_> {
+    fn size(&self) -> usize {
+        self.layout.header.dense_len
+    }
+
+    fn start(&self, q: usize) -> Option<DenseInterval> {
+        self.option(self.layout.start + 2 * q)
+    }
+
+    fn start2(&self, q: usize) -> Option<DenseInterval> {
+        self.option(self.layout.start2 + 2 * q)
+    }
+
+    fn parent(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.lookup(self.layout.parent, self.layout.header.parent_len, a)
+    }
+
+    fn left(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.lookup(self.layout.left, self.layout.header.left_len, a)
+    }
+
+    fn last(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        self.lookup(self.layout.last, self.layout.header.last_len, a)
+    }
+
+    fn smaller(&self, a: &DenseInterval) -> impl DoubleEndedIterator<Item = DenseInterval> + '_ {
+        self.smaller_run(a).unwrap_or(0..0).map(|i| self.pool(i))
+    }
+}
+
+/// Serialize an index, header and payload, to a single buffer.
+///
+pub(crate) fn encode(s: &Stabby) -> Vec<u8> {
+    let (domain, dense) = s.layers();
+    let mut payload: Vec<u8> = Vec::new();
+
+    let k = domain.len();
+    for x in domain.iter() {
+        payload.extend_from_slice(&x.to_le_bytes());
+    }
+    for x in dense.start.iter() {
+        put_option(&mut payload, x);
+    }
+    for x in dense.start2.iter() {
+        put_option(&mut payload, x);
+    }
+    for table in [&dense.parent, &dense.left, &dense.last] {
+        let mut entries: Vec<(&DenseInterval, &DenseInterval)> = table.iter().collect();
+        entries.sort();
+        for (k, v) in entries {
+            put_interval(&mut payload, k);
+            put_interval(&mut payload, v);
+        }
+    }
+    let mut keys: Vec<&DenseInterval> = dense.smaller.keys().collect();
+    keys.sort();
+    let mut offset: usize = 0;
+    for k in keys.iter() {
+        let n = dense.smaller[*k].len();
+        put_interval(&mut payload, k);
+        payload.extend_from_slice(&(offset as u64).to_le_bytes());
+        payload.extend_from_slice(&(n as u64).to_le_bytes());
+        offset += n;
+    }
+    for k in keys.iter() {
+        for x in dense.smaller[*k].iter() {
+            put_interval(&mut payload, x);
+        }
+    }
+
+    let header = Header {
+        checksum: 0,
+        fingerprint: s.fingerprint(),
+        domain_len: k,
+        interval_count: dense.parent.len() + offset,
+        dense_len: dense.start.len(),
+        parent_len: dense.parent.len(),
+        left_len: dense.left.len(),
+        last_len: dense.last.len(),
+        smaller_len: keys.len(),
+        pool_len: offset,
+    };
+    let mut buf = header.encode();
+    buf.extend_from_slice(&payload);
+    let mut h = Fnv64::new();
+    h.update(&buf[CHECKSUM_START..]);
+    buf[16..24].copy_from_slice(&h.finish().to_le_bytes());
+    buf
+}
+
+/// Write a set of named indexes to a single file, in the format described
+/// [above](crate::format#index-sets). The names must be distinct.
+///
+pub fn write_indexes<'a, I>(w: &mut impl Write, indexes: I) -> Result<(), StabbyError>
+where
+    I: IntoIterator<Item = (&'a str, &'a Stabby)>,
+{
+    let mut items: Vec<(&str, Vec<u8>)> = Vec::new();
+    for (name, s) in indexes {
+        items.push((name, encode(s)));
+    }
+    items.sort_by(|a, b| a.0.cmp(b.0));
+    for pair in items.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            return Err(StabbyError::DuplicateName(pair[0].0.to_string()));
+        }
+    }
+
+    let padded = |n: usize| n.div_ceil(8) * 8;
+    let mut offset = SET_HEADER_LEN;
+    for (name, _) in items.iter() {
+        offset += 24 + padded(name.len());
+    }
+
+    let mut buf: Vec<u8> = Vec::new();
+    buf.extend_from_slice(&SET_MAGIC);
+    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    buf.extend_from_slice(&(items.len() as u64).to_le_bytes());
+    for (name, bytes) in items.iter() {
+        buf.extend_from_slice(&(name.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&(offset as u64).to_le_bytes());
+        buf.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        buf.extend_from_slice(name.as_bytes());
+        buf.resize(padded(buf.len()), 0);
+        offset += padded(bytes.len());
+    }
+    w.write_all(&buf)?;
+    for (_, bytes) in items.iter() {
+        w.write_all(bytes)?;
+        w.write_all(&[0; 8][..padded(bytes.len()) - bytes.len()])?;
+    }
+    Ok(())
+}
+
+/// An entry in the table of contents of a set of indexes.
+///
+pub(crate) struct SetEntry {
+    pub(crate) name: String,
+    pub(crate) range: Range<usize>,
+}
+
+/// Decode and check the table of contents of a set of indexes. The indexes
+/// themselves are not examined.
+///
+pub(crate) fn parse_set(bytes: &[u8]) -> Result<Vec<SetEntry>, StabbyError> {
+    if bytes.len() < SET_MAGIC.len() {
+        return Err(StabbyError::Truncated);
+    }
+    if bytes[0..SET_MAGIC.len()] != SET_MAGIC {
+        return Err(StabbyError::BadMagic);
+    }
+    if bytes.len() < SET_HEADER_LEN {
+        return Err(StabbyError::Truncated);
+    }
+    let version = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    if version > FORMAT_VERSION {
+        return Err(StabbyError::UnsupportedVersion {
+            found: version,
+            supported: FORMAT_VERSION,
+        });
+    }
+    if version == 0 || bytes[12..16] != [0; 4] {
+        return Err(corrupt("bad index set header"));
+    }
+
+    let count = get_usize(bytes, 16)?;
+    let mut entries: Vec<SetEntry> = Vec::new();
+    let mut pos = SET_HEADER_LEN;
+    for _ in 0..count {
+        if bytes.len() < pos + 24 {
+            return Err(StabbyError::Truncated);
+        }
+        let name_len = get_usize(bytes, pos)?;
+        let offset = get_usize(bytes, pos + 8)?;
+        let len = get_usize(bytes, pos + 16)?;
+        pos += 24;
+        let name_end = pos
+            .checked_add(name_len)
+            .ok_or_else(|| corrupt("name too long"))?;
+        if bytes.len() < name_end {
+            return Err(StabbyError::Truncated);
+        }
+        let name = std::str::from_utf8(&bytes[pos..name_end])
+            .map_err(|_| corrupt("index name is not UTF-8"))?
+            .to_string();
+        pos += name_len.div_ceil(8) * 8;
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| corrupt("index out of range"))?;
+        if bytes.len() < end {
+            return Err(StabbyError::Truncated);
+        }
+        if let Some(prev) = entries.last() {
+            if prev.name >= name {
+                return Err(corrupt("index names are not sorted"));
+            }
+        }
+        entries.push(SetEntry {
+            name,
+            range: offset..end,
+        });
+    }
+    Ok(entries)
+}
+
+impl Stabby {
+    /// Write the index in the binary format described in [`format`](crate::format).
+    ///
+    pub fn write_to(&self, w: &mut impl Write) -> Result<(), StabbyError> {
+        w.write_all(&encode(self))?;
+        Ok(())
+    }
+
+    /// Read an index previously written with [`write_to`](Stabby::write_to).
+    ///
+    /// The checksum, the structure of the index, and its fingerprint are all
+    /// verified, so a damaged or truncated input results in an error. The
+    /// structural checks guarantee that queries on the index terminate, but
+    /// not that a file crafted to pass them answers queries correctly.
+    ///
+    pub fn read_from(r: &mut impl Read) -> Result<Stabby, StabbyError> {
+        let mut buf: Vec<u8> = vec![0; MAGIC.len()];
+        r.read_exact(&mut buf)?;
+        if buf[..] != MAGIC {
+            return Err(StabbyError::BadMagic);
+        }
+        buf.resize(HEADER_LEN, 0);
+        r.read_exact(&mut buf[MAGIC.len()..])?;
+        let header = Header::parse(&buf)?;
+        let len = header.payload_len()?;
+        r.take(len as u64).read_to_end(&mut buf)?;
+
+        let raw = RawIndex::new(&buf)?;
+        raw.check_fingerprint()?;
+        Ok(raw.to_stabby())
+    }
+
+    /// A 64 bit fingerprint of the intervals held in the index.
+    ///
+    /// The fingerprint depends only on the set of intervals, so it can be
+    /// used to recognise indexes built from the same data, for example as
+    /// a cache key.
+    ///
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint(self.intervals().into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sparse::Repr,
+        test_util::{intervals, many_intervals},
+    };
+
+    fn mutyh() -> Vec<Interval> {
+        vec![
+            Interval::new(45329163, 45329437),
+            Interval::new(45330516, 45330557),
+            Interval::new(45331182, 45331334),
+            Interval::new(45331420, 45331556),
+            Interval::new(45332763, 45332834),
+            Interval::new(45334391, 45334511),
+            Interval::new(45340219, 45340447),
+        ]
+    }
+
+    fn round_trip(s: &Stabby) -> Stabby {
+        let mut buf: Vec<u8> = Vec::new();
+        s.write_to(&mut buf).unwrap();
+        Stabby::read_from(&mut buf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_1() {
+        for src in [intervals(), many_intervals()] {
+            let s = Stabby::new(&src);
+            let t = round_trip(&s);
+            assert_eq!(t.fingerprint(), s.fingerprint());
+            assert_eq!(matches!(t.repr, Repr::Small(_)), src.len() <= SMALL_MAX);
+            for q in 0..600 {
+                assert_eq!(t.stab(q), s.stab(q));
+                assert_eq!(t.stabs(q), s.stabs(q));
+                let qi = Interval::new(q, q + 17);
+                assert_eq!(t.stab_interval(&qi), s.stab_interval(&qi));
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_empty() {
+        let s = Stabby::new(&[]);
+        let t = round_trip(&s);
+        assert!(!t.stabs(0));
+        assert_eq!(t.stab(10), vec![]);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let a = Stabby::new(&mutyh());
+        let b = Stabby::new(&mutyh());
+        let c = Stabby::new(&intervals());
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
+    #[test]
+    fn test_truncated() {
+        for xs in [mutyh(), many_intervals()] {
+            let mut buf: Vec<u8> = Vec::new();
+            Stabby::new(&xs).write_to(&mut buf).unwrap();
+            for n in [0, 4, 50, HEADER_LEN, buf.len() - 1] {
+                let res = Stabby::read_from(&mut &buf[..n]);
+                assert!(matches!(res, Err(StabbyError::Truncated)), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let mut buf: Vec<u8> = Vec::new();
+        Stabby::new(&mutyh()).write_to(&mut buf).unwrap();
+        buf[0] = b'X';
+        let res = Stabby::read_from(&mut buf.as_slice());
+        assert!(matches!(res, Err(StabbyError::BadMagic)));
+    }
+
+    #[test]
+    fn test_future_version() {
+        let mut buf: Vec<u8> = Vec::new();
+        Stabby::new(&mutyh()).write_to(&mut buf).unwrap();
+        buf[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let res = Stabby::read_from(&mut buf.as_slice());
+        assert!(matches!(
+            res,
+            Err(StabbyError::UnsupportedVersion { found, supported: FORMAT_VERSION })
+                if found == FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        for xs in [intervals(), many_intervals()] {
+            let mut buf: Vec<u8> = Vec::new();
+            Stabby::new(&xs).write_to(&mut buf).unwrap();
+            let n = buf.len();
+            buf[n - 3] ^= 0x40;
+            let res = Stabby::read_from(&mut buf.as_slice());
+            assert!(matches!(res, Err(StabbyError::ChecksumMismatch { .. })));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_structure() {
+        for xs in [intervals(), many_intervals()] {
+            let mut buf: Vec<u8> = Vec::new();
+            Stabby::new(&xs).write_to(&mut buf).unwrap();
+
+            // Make the domain unsorted, and patch up the checksum so that
+            // only the structural validation can catch it.
+            buf[HEADER_LEN + 8..HEADER_LEN + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+            patch_checksum(&mut buf);
+
+            let res = Stabby::read_from(&mut buf.as_slice());
+            assert!(matches!(res, Err(StabbyError::Corrupt(_))));
+        }
+    }
+
+    fn patch_checksum(buf: &mut [u8]) {
+        let mut h = Fnv64::new();
+        h.update(&buf[CHECKSUM_START..]);
+        buf[16..24].copy_from_slice(&h.finish().to_le_bytes());
+    }
+
+    #[test]
+    fn test_corrupt_walk() {
+        let mut buf: Vec<u8> = Vec::new();
+        Stabby::new(&many_intervals()).write_to(&mut buf).unwrap();
+        let layout = RawIndex::new(&buf).unwrap().layout();
+        assert!(layout.header.left_len > 0 && layout.header.last_len > 1);
+
+        // Point a left sibling back at an interval that ends no earlier,
+        // which could send a walk around a cycle.
+        let mut bad = buf.clone();
+        let i = HEADER_LEN + 8 * layout.left;
+        let last = bad[i + 8..i + 16].to_vec();
+        bad[i + 24..i + 32].copy_from_slice(&last);
+        patch_checksum(&mut bad);
+        let res = Stabby::read_from(&mut bad.as_slice());
+        assert!(matches!(res, Err(StabbyError::Corrupt(_))));
+
+        // Likewise make a last child end after its parent. The first entry
+        // is for the zero interval, whose last child is unconstrained.
+        let mut bad = buf.clone();
+        let i = HEADER_LEN + 8 * (layout.last + 4);
+        let d = 2 * layout.header.domain_len as u64 - 2;
+        bad[i + 24..i + 32].copy_from_slice(&d.to_le_bytes());
+        patch_checksum(&mut bad);
+        let res = Stabby::read_from(&mut bad.as_slice());
+        assert!(matches!(res, Err(StabbyError::Corrupt(_))));
+
+        // And make start2 name an interval beginning after its position.
+        let mut bad = buf;
+        let i = HEADER_LEN + 8 * layout.start2;
+        let top = layout.header.dense_len - 1;
+        let a = RawIndex::new(&bad).unwrap().start2(top).unwrap();
+        bad[i..i + 8].copy_from_slice(&(a.first as u64).to_le_bytes());
+        bad[i + 8..i + 16].copy_from_slice(&(a.last as u64).to_le_bytes());
+        patch_checksum(&mut bad);
+        let res = Stabby::read_from(&mut bad.as_slice());
+        assert!(matches!(res, Err(StabbyError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+
+use crate::{domain::DomainMap, sparse::Repr, Interval, Stabby};
+
+impl<D: DomainMap> Stabby<D> {
+    /// Find the intervals whose start lies within `start_tol` of the start
+    /// of `x`, and whose end lies within `end_tol` of its end, for matching
+    /// features such as splice junctions whose reported endpoints disagree
+    /// by a few positions.
+    ///
+    /// The results are ranked by the total distance of their endpoints
+    /// from those of `x`, nearest first, and ties in sorted order.
+    ///
+    pub fn match_fuzzy(&self, x: &Interval, start_tol: u64, end_tol: u64) -> Vec<Interval> {
+        let lo = x.first.saturating_sub(start_tol);
+        let hi = x.first.saturating_add(start_tol);
+        let end_ok = |y: &Interval| y.last.abs_diff(x.last) <= end_tol;
+
+        // Candidates are found from the starts that lie within tolerance,
+        // which are a run of the domain.
+        let mut res: Vec<Interval> = match &self.repr {
+            Repr::Small(xs) => {
+                let i = xs.partition_point(|y| y.first < lo);
+                xs[i..]
+                    .iter()
+                    .take_while(|y| y.first <= hi)
+                    .filter(|y| end_ok(y))
+                    .copied()
+                    .collect()
+            }
+            Repr::Full { domain, .. } => {
+                let mut res: Vec<Interval> = Vec::new();
+                for r in domain.rank(lo)..domain.count() {
+                    let p = domain.select(r);
+                    if p > hi {
+                        break;
+                    }
+                    res.extend(self.with_start(p).into_iter().filter(|y| end_ok(y)));
+                }
+                res
+            }
+        };
+        let distance =
+            |y: &Interval| y.first.abs_diff(x.first) as u128 + y.last.abs_diff(x.last) as u128;
+        res.sort_by_key(|y| (distance(y), *y));
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_fuzzy() {
+        let junctions = vec![
+            Interval::new(1000, 2000),
+            Interval::new(1002, 1999),
+            Interval::new(1003, 2010),
+            Interval::new(1010, 2000),
+            Interval::new(1500, 1600),
+        ];
+        let s = Stabby::new(&junctions);
+        assert_eq!(
+            s.match_fuzzy(&Interval::new(1001, 2000), 3, 3),
+            vec![Interval::new(1000, 2000), Interval::new(1002, 1999)]
+        );
+        assert_eq!(
+            s.match_fuzzy(&Interval::new(1001, 2000), 10, 10),
+            vec![
+                Interval::new(1000, 2000),
+                Interval::new(1002, 1999),
+                Interval::new(1010, 2000),
+                Interval::new(1003, 2010),
+            ]
+        );
+        assert!(s.match_fuzzy(&Interval::new(1001, 2000), 0, 0).is_empty());
+        assert_eq!(
+            s.match_fuzzy(&Interval::new(0, u64::MAX), u64::MAX, u64::MAX)
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_match_fuzzy_full() {
+        let xs: Vec<Interval> = (0..400u64)
+            .map(|i| Interval::new(i * 5, i * 5 + 20 + i % 9))
+            .collect();
+        let s = Stabby::new(&xs);
+        for q in [
+            Interval::new(0, 20),
+            Interval::new(503, 530),
+            Interval::new(1990, 2030),
+        ] {
+            for (st, et) in [(0, 0), (2, 3), (7, 1), (20, 20)] {
+                let mut want: Vec<Interval> = xs
+                    .iter()
+                    .filter(|y| y.first.abs_diff(q.first) <= st && y.last.abs_diff(q.last) <= et)
+                    .copied()
+                    .collect();
+                want.sort_by_key(|y| (y.first.abs_diff(q.first) + y.last.abs_diff(q.last), *y));
+                assert_eq!(s.match_fuzzy(&q, st, et), want);
+            }
+        }
+    }
+}
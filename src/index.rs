@@ -0,0 +1,175 @@
+use alloc::vec::Vec;
+
+use crate::{FromPoints, Interval, MaybeSync, Stabby};
+
+/// The queries common to the interval indexes in this crate, so that code
+/// can be written against any of them, and the structures compared.
+///
+/// All of the indexes hold a set of intervals, built from a list in
+/// natural sorted order and free of duplicates, and return their results
+/// in sorted order.
+///
+pub trait StabbingIndex {
+    /// Build an index over the given intervals, which must be in natural
+    /// sorted order, and free of duplicates.
+    fn build(xs: &[Interval]) -> Self
+    where
+        Self: Sized;
+
+    /// Test if a position stabs any intervals.
+    fn stabs(&self, q: u64) -> bool {
+        !self.stab(q).is_empty()
+    }
+
+    /// Retrieve the list of intervals that are stabbed by the given
+    /// position, in sorted order.
+    fn stab(&self, q: u64) -> Vec<Interval>;
+
+    /// Find the list of intervals that intersect the given query interval,
+    /// in sorted order.
+    fn stab_interval(&self, q: &Interval) -> Vec<Interval>;
+
+    /// Count the intervals that are stabbed by the given position.
+    fn count(&self, q: u64) -> usize {
+        self.stab(q).len()
+    }
+
+    /// Count the intervals that intersect the given query interval.
+    fn count_interval(&self, q: &Interval) -> usize {
+        self.stab_interval(q).len()
+    }
+}
+
+impl<D: FromPoints + MaybeSync> StabbingIndex for Stabby<D> {
+    fn build(xs: &[Interval]) -> Self {
+        Stabby::with_domain(xs)
+    }
+
+    fn stabs(&self, q: u64) -> bool {
+        Stabby::stabs(self, q)
+    }
+
+    fn stab(&self, q: u64) -> Vec<Interval> {
+        Stabby::stab(self, q)
+    }
+
+    fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        Stabby::stab_interval(self, q)
+    }
+}
+
+/// An index that answers every query by scanning all of its intervals.
+///
+/// It takes no time to build and no space beyond the intervals, and for a
+/// handful of intervals it is as quick as anything. It also serves as the
+/// reference against which the other indexes are checked.
+///
+#[derive(Clone, Debug, Default)]
+pub struct NaiveIndex {
+    xs: Vec<Interval>,
+}
+
+impl StabbingIndex for NaiveIndex {
+    fn build(xs: &[Interval]) -> Self {
+        NaiveIndex { xs: xs.to_vec() }
+    }
+
+    fn stabs(&self, q: u64) -> bool {
+        self.xs.iter().any(|x| x.first <= q && q <= x.last)
+    }
+
+    fn stab(&self, q: u64) -> Vec<Interval> {
+        self.xs
+            .iter()
+            .filter(|x| x.first <= q && q <= x.last)
+            .copied()
+            .collect()
+    }
+
+    fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        self.xs
+            .iter()
+            .filter(|x| x.first <= q.last && q.first <= x.last)
+            .copied()
+            .collect()
+    }
+
+    fn count(&self, q: u64) -> usize {
+        self.xs
+            .iter()
+            .filter(|x| x.first <= q && q <= x.last)
+            .count()
+    }
+
+    fn count_interval(&self, q: &Interval) -> usize {
+        self.xs
+            .iter()
+            .filter(|x| x.first <= q.last && q.first <= x.last)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::MiniRng, EytzingerDomain, IntervalTree, NcList};
+
+    /// Check an index against the naive one, over intervals both long and
+    /// short, many of them nested.
+    fn check_backend<I: StabbingIndex>() {
+        for seed in 1..=3 {
+            let mut rng = MiniRng::new(seed);
+            let mut xs: Vec<Interval> = (0..400)
+                .map(|_| {
+                    let first = rng.rnd() % 1000;
+                    let len = if rng.rnd().is_multiple_of(10) {
+                        400
+                    } else {
+                        30
+                    };
+                    Interval::new(first, first + rng.rnd() % len)
+                })
+                .collect();
+            xs.sort_unstable();
+            xs.dedup();
+            let naive = NaiveIndex::build(&xs);
+            let idx = I::build(&xs);
+            for q in 0..1450 {
+                assert_eq!(idx.stabs(q), naive.stabs(q));
+                assert_eq!(idx.stab(q), naive.stab(q));
+                assert_eq!(idx.count(q), naive.count(q));
+                for w in [0, 5, 50] {
+                    let qi = Interval::new(q, q + w);
+                    assert_eq!(idx.stab_interval(&qi), naive.stab_interval(&qi));
+                    assert_eq!(idx.count_interval(&qi), naive.count_interval(&qi));
+                }
+            }
+        }
+        let empty = I::build(&[]);
+        assert!(!empty.stabs(0));
+        assert!(empty.stab_interval(&Interval::new(0, u64::MAX)).is_empty());
+    }
+
+    #[test]
+    fn test_backends() {
+        check_backend::<Stabby>();
+        check_backend::<Stabby<EytzingerDomain>>();
+        check_backend::<NcList>();
+        check_backend::<IntervalTree>();
+    }
+
+    #[test]
+    fn test_dyn() {
+        let xs = [Interval::new(1, 5), Interval::new(3, 9)];
+        let backends: Vec<Box<dyn StabbingIndex>> = vec![
+            Box::new(<Stabby>::build(&xs)),
+            Box::new(NaiveIndex::build(&xs)),
+            Box::new(NcList::build(&xs)),
+            Box::new(IntervalTree::build(&xs)),
+        ];
+        for b in backends.iter() {
+            assert_eq!(b.count(4), 2);
+            assert_eq!(b.stab(7), vec![Interval::new(3, 9)]);
+        }
+    }
+}
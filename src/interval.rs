@@ -0,0 +1,225 @@
+use alloc::string::ToString;
+use core::str::FromStr;
+
+use crate::{Interval, StabbyError};
+
+impl Interval {
+    /// The number of positions in the interval.
+    ///
+    /// The interval covering every `u64` has 2^64 positions, one more than
+    /// a `u64` can hold, so its length is given as `u64::MAX`.
+    ///
+    // An interval always holds at least one position, so `is_empty` would
+    // always be false.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        (self.last - self.first).saturating_add(1)
+    }
+
+    /// Test if the interval contains a position.
+    ///
+    pub fn contains(&self, q: u64) -> bool {
+        self.first <= q && q <= self.last
+    }
+
+    /// Test if the interval contains the whole of another.
+    ///
+    pub fn contains_interval(&self, other: &Interval) -> bool {
+        self.first <= other.first && other.last <= self.last
+    }
+
+    /// Test if the interval shares any positions with another.
+    ///
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.first <= other.last && other.first <= self.last
+    }
+
+    /// The positions common to the interval and another, if any.
+    ///
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        self.overlaps(other)
+            .then(|| Interval::new(self.first.max(other.first), self.last.min(other.last)))
+    }
+
+    /// The smallest interval containing both the interval and another.
+    ///
+    pub fn hull(&self, other: &Interval) -> Interval {
+        Interval::new(self.first.min(other.first), self.last.max(other.last))
+    }
+
+    /// The distance between the nearest endpoints of the interval and
+    /// another: 0 if they overlap, and 1 if they are adjacent.
+    ///
+    pub fn distance(&self, other: &Interval) -> u64 {
+        if self.overlaps(other) {
+            0
+        } else {
+            other
+                .first
+                .abs_diff(self.last)
+                .min(self.first.abs_diff(other.last))
+        }
+    }
+
+    /// Move the interval by the given offset, or `None` if it would move
+    /// past either end of the `u64` domain.
+    ///
+    pub fn shift(&self, delta: i64) -> Option<Interval> {
+        Some(Interval::new(
+            self.first.checked_add_signed(delta)?,
+            self.last.checked_add_signed(delta)?,
+        ))
+    }
+
+    /// Widen the interval by the given amount on each side, stopping at
+    /// the ends of the `u64` domain.
+    ///
+    pub fn expand(&self, by: u64) -> Interval {
+        self.slop(by, by)
+    }
+
+    /// Widen the interval by different amounts before its start and after
+    /// its end, stopping at the ends of the `u64` domain.
+    ///
+    pub fn slop(&self, before: u64, after: u64) -> Interval {
+        Interval::new(
+            self.first.saturating_sub(before),
+            self.last.saturating_add(after),
+        )
+    }
+
+    /// Split the interval into the positions before `q`, and those from
+    /// `q` on. Either part is `None` if it would be empty.
+    ///
+    pub fn split_at(&self, q: u64) -> (Option<Interval>, Option<Interval>) {
+        if q <= self.first {
+            (None, Some(*self))
+        } else if q > self.last {
+            (Some(*self), None)
+        } else {
+            (
+                Some(Interval::new(self.first, q - 1)),
+                Some(Interval::new(q, self.last)),
+            )
+        }
+    }
+}
+
+/// Parse an interval as written by its `Display` implementation, such as
+/// `[10, 20]`. Spaces around the numbers are ignored.
+///
+impl FromStr for Interval {
+    type Err = StabbyError;
+
+    fn from_str(s: &str) -> Result<Interval, StabbyError> {
+        let err = |reason| StabbyError::Parse {
+            input: s.to_string(),
+            reason,
+        };
+        let inner = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|t| t.strip_suffix(']'))
+            .ok_or_else(|| err("expected an interval such as [10, 20]"))?;
+        let (first, last) = inner
+            .split_once(',')
+            .ok_or_else(|| err("expected two positions separated by a comma"))?;
+        let first: u64 = first
+            .trim()
+            .parse()
+            .map_err(|_| err("invalid start position"))?;
+        let last: u64 = last
+            .trim()
+            .parse()
+            .map_err(|_| err("invalid end position"))?;
+        if first > last {
+            return Err(err("start is after end"));
+        }
+        Ok(Interval::new(first, last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    const MAX: u64 = u64::MAX;
+
+    #[test]
+    fn test_len_contains() {
+        let x = Interval::new(10, 20);
+        assert_eq!(x.len(), 11);
+        assert_eq!(Interval::new(5, 5).len(), 1);
+        assert_eq!(Interval::new(0, MAX).len(), MAX);
+        assert_eq!(Interval::new(1, MAX).len(), MAX);
+        assert!(x.contains(10) && x.contains(20) && !x.contains(21) && !x.contains(9));
+        assert!(x.contains_interval(&Interval::new(10, 15)));
+        assert!(!x.contains_interval(&Interval::new(5, 15)));
+        assert!(Interval::new(0, MAX).contains_interval(&x));
+    }
+
+    #[test]
+    fn test_overlap_hull_distance() {
+        let x = Interval::new(10, 20);
+        let y = Interval::new(20, 30);
+        let z = Interval::new(25, MAX);
+        assert!(x.overlaps(&y) && !x.overlaps(&z));
+        assert_eq!(x.intersection(&y), Some(Interval::new(20, 20)));
+        assert_eq!(x.intersection(&z), None);
+        assert_eq!(x.hull(&z), Interval::new(10, MAX));
+        assert_eq!(x.distance(&y), 0);
+        assert_eq!(x.distance(&z), 5);
+        assert_eq!(z.distance(&x), 5);
+        assert_eq!(x.distance(&Interval::new(21, 22)), 1);
+        assert_eq!(Interval::new(0, 0).distance(&Interval::new(MAX, MAX)), MAX);
+    }
+
+    #[test]
+    fn test_shift_expand_split() {
+        let x = Interval::new(10, 20);
+        assert_eq!(x.shift(5), Some(Interval::new(15, 25)));
+        assert_eq!(x.shift(-10), Some(Interval::new(0, 10)));
+        assert_eq!(x.shift(-11), None);
+        assert_eq!(Interval::new(MAX - 1, MAX).shift(1), None);
+        assert_eq!(x.expand(3), Interval::new(7, 23));
+        assert_eq!(x.expand(MAX), Interval::new(0, MAX));
+        assert_eq!(x.slop(20, 1), Interval::new(0, 21));
+        assert_eq!(x.split_at(10), (None, Some(x)));
+        assert_eq!(x.split_at(21), (Some(x), None));
+        assert_eq!(
+            x.split_at(15),
+            (Some(Interval::new(10, 14)), Some(Interval::new(15, 20)))
+        );
+        assert_eq!(
+            Interval::new(0, MAX).split_at(MAX).1,
+            Some(Interval::new(MAX, MAX))
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let x = Interval::new(45_331_182, 45_331_334);
+        assert_eq!(x.to_string().parse::<Interval>().unwrap(), x);
+        assert_eq!(
+            " [ 3 ,4 ] ".parse::<Interval>().unwrap(),
+            Interval::new(3, 4)
+        );
+        let m = Interval::new(0, MAX);
+        assert_eq!(m.to_string().parse::<Interval>().unwrap(), m);
+        for bad in [
+            "3, 4",
+            "[3 4]",
+            "[a, 4]",
+            "[3, -4]",
+            "[5, 4]",
+            "[0, 18446744073709551616]",
+        ] {
+            match bad.parse::<Interval>() {
+                Err(StabbyError::Parse { input, .. }) => assert_eq!(input, bad),
+                other => panic!("{:?} parsed as {:?}", bad, other),
+            }
+        }
+    }
+}
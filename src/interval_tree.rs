@@ -0,0 +1,129 @@
+use alloc::vec::Vec;
+
+use crate::{index::StabbingIndex, Interval};
+
+/// An augmented interval tree: a balanced binary search tree over the
+/// intervals in sorted order, with each node holding the greatest end of
+/// any interval beneath it, so that subtrees ending before a query can be
+/// skipped.
+///
+/// The tree is implicit in the sorted list of intervals, the root of each
+/// range being its middle element, so it needs no pointers, and only one
+/// extra word per interval.
+///
+#[derive(Clone, Debug, Default)]
+pub struct IntervalTree {
+    xs: Vec<Interval>,
+    max_last: Vec<u64>,
+}
+
+impl IntervalTree {
+    /// Fill in the greatest ends for the subtree over the range, returning
+    /// its greatest end.
+    fn augment(&mut self, lo: usize, hi: usize) -> u64 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let m = self.xs[mid]
+            .last
+            .max(self.augment(lo, mid))
+            .max(self.augment(mid + 1, hi));
+        self.max_last[mid] = m;
+        m
+    }
+
+    /// Visit the intervals in the range intersecting the query, in order,
+    /// stopping early if the visitor returns `false`.
+    fn visit<F: FnMut(&Interval) -> bool>(
+        &self,
+        lo: usize,
+        hi: usize,
+        q: &Interval,
+        f: &mut F,
+    ) -> bool {
+        if lo >= hi {
+            return true;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_last[mid] < q.first {
+            return true;
+        }
+        if !self.visit(lo, mid, q, f) {
+            return false;
+        }
+        let x = &self.xs[mid];
+        if x.first > q.last {
+            return true;
+        }
+        if x.last >= q.first && !f(x) {
+            return false;
+        }
+        self.visit(mid + 1, hi, q, f)
+    }
+}
+
+impl StabbingIndex for IntervalTree {
+    fn build(xs: &[Interval]) -> Self {
+        let mut t = IntervalTree {
+            xs: xs.to_vec(),
+            max_last: alloc::vec![0; xs.len()],
+        };
+        t.augment(0, xs.len());
+        t
+    }
+
+    fn stabs(&self, q: u64) -> bool {
+        !self.visit(0, self.xs.len(), &Interval::new(q, q), &mut |_| false)
+    }
+
+    fn stab(&self, q: u64) -> Vec<Interval> {
+        self.stab_interval(&Interval::new(q, q))
+    }
+
+    fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        let mut res: Vec<Interval> = Vec::new();
+        self.visit(0, self.xs.len(), q, &mut |x| {
+            res.push(*x);
+            true
+        });
+        res
+    }
+
+    fn count(&self, q: u64) -> usize {
+        self.count_interval(&Interval::new(q, q))
+    }
+
+    fn count_interval(&self, q: &Interval) -> usize {
+        let mut n = 0;
+        self.visit(0, self.xs.len(), q, &mut |_| {
+            n += 1;
+            true
+        });
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_augment() {
+        let xs = vec![
+            Interval::new(10, 100),
+            Interval::new(10, 200),
+            Interval::new(20, 30),
+            Interval::new(25, 150),
+            Interval::new(40, 50),
+        ];
+        let t = IntervalTree::build(&xs);
+        assert_eq!(t.max_last, vec![100, 200, 200, 150, 150]);
+        assert!(t.stabs(160));
+        assert!(!t.stabs(201));
+        assert_eq!(
+            t.stab(120),
+            vec![Interval::new(10, 200), Interval::new(25, 150)]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    ops::RangeBounds,
+    slice,
+};
+
+use crate::{
+    dense::{DenseInterval, DenseLookup, DenseStabby},
+    domain::{to_dense, to_sparse, Domain, DomainMap, FromPoints},
+    sparse::{closed_range, Repr},
+    Interval, Stabby,
+};
+
+/// An iterator over the intervals of a [`Stabby`], in sorted order, as
+/// returned by [`Stabby::iter`].
+///
+pub struct Iter<'a, D = Domain> {
+    inner: Inner<'a, D>,
+    remaining: usize,
+}
+
+enum Inner<'a, D> {
+    Small(slice::Iter<'a, Interval>),
+    Full {
+        domain: &'a D,
+        dense: &'a DenseStabby,
+        // The next position to look for intervals starting there, and the
+        // intervals found at the last one, shortest first.
+        p: usize,
+        run: slice::Iter<'a, DenseInterval>,
+        longest: Option<DenseInterval>,
+    },
+}
+
+impl<D: DomainMap> Iterator for Iter<'_, D> {
+    type Item = Interval;
+
+    fn next(&mut self) -> Option<Interval> {
+        let res = match &mut self.inner {
+            Inner::Small(xs) => xs.next().copied(),
+            Inner::Full {
+                domain,
+                dense,
+                p,
+                run,
+                longest,
+            } => loop {
+                if let Some(y) = run.next().copied().or_else(|| longest.take()) {
+                    break Some(Interval::new(
+                        to_sparse(*domain, y.first),
+                        to_sparse(*domain, y.last),
+                    ));
+                }
+                if *p >= dense.size() {
+                    break None;
+                }
+                if let Some(a) = dense.start(*p).filter(|a| a.first == *p) {
+                    *run = dense.smaller.get(&a).map_or(&[][..], |v| &v[..]).iter();
+                    *longest = Some(a);
+                }
+                *p += 1;
+            },
+        };
+        if res.is_some() {
+            self.remaining -= 1;
+        }
+        res
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<D: DomainMap> ExactSizeIterator for Iter<'_, D> {}
+
+impl<D: DomainMap> FusedIterator for Iter<'_, D> {}
+
+impl<D: DomainMap> Stabby<D> {
+    /// Iterate over the intervals in the index, in sorted order.
+    ///
+    pub fn iter(&self) -> Iter<'_, D> {
+        match &self.repr {
+            Repr::Small(xs) => Iter {
+                inner: Inner::Small(xs.iter()),
+                remaining: xs.len(),
+            },
+            Repr::Full { domain, dense, len } => Iter {
+                inner: Inner::Full {
+                    domain,
+                    dense,
+                    p: 0,
+                    run: [].iter(),
+                    longest: None,
+                },
+                remaining: *len,
+            },
+        }
+    }
+
+    /// The number of intervals in the index.
+    ///
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Small(xs) => xs.len(),
+            Repr::Full { len, .. } => *len,
+        }
+    }
+
+    /// Test if the index holds no intervals.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find the intervals that start within a range of positions, in
+    /// sorted order.
+    ///
+    /// This is a scan of the sorted intervals from the first starting in
+    /// the range, so it takes time in the number found, and the endpoints
+    /// within the range, rather than in all those overlapping it.
+    ///
+    pub fn starting_in<R: RangeBounds<u64>>(&self, range: R) -> Vec<Interval> {
+        let r = match closed_range(&range) {
+            None => return Vec::new(),
+            Some(r) => r,
+        };
+        let (domain, dense) = match &self.repr {
+            Repr::Small(xs) => {
+                let i = xs.partition_point(|x| x.first < r.first);
+                return xs[i..]
+                    .iter()
+                    .take_while(|x| x.first <= r.last)
+                    .copied()
+                    .collect();
+            }
+            Repr::Full { domain, dense, .. } => (domain, dense),
+        };
+        // Intervals start at points of the domain, each held with the
+        // shorter ones that share its start.
+        let lo = to_dense(domain, r.first);
+        let hi = to_dense(domain, r.last).min(dense.size() - 1);
+        let mut res = Vec::new();
+        for p in lo..=hi {
+            if let Some(a) = dense.start(p).filter(|a| a.first == p) {
+                let run = dense.smaller.get(&a).map_or(&[][..], |v| &v[..]);
+                for y in run.iter().chain([&a]) {
+                    res.push(Interval::new(
+                        to_sparse(domain, y.first),
+                        to_sparse(domain, y.last),
+                    ));
+                }
+            }
+        }
+        res
+    }
+
+    /// Find the intervals that end within a range of positions, in sorted
+    /// order.
+    ///
+    /// The index is not ordered by end, so these are found among the
+    /// intervals overlapping the range, and the query takes time in the
+    /// number of those, as for [`stab_interval`](Stabby::stab_interval),
+    /// however few end within it.
+    ///
+    pub fn ending_in<R: RangeBounds<u64>>(&self, range: R) -> Vec<Interval> {
+        // Every such interval intersects the range.
+        match closed_range(&range) {
+            None => Vec::new(),
+            Some(r) => self
+                .stab_interval(&r)
+                .into_iter()
+                .filter(|x| x.last <= r.last)
+                .collect(),
+        }
+    }
+}
+
+impl<'a, D: DomainMap> IntoIterator for &'a Stabby<D> {
+    type Item = Interval;
+    type IntoIter = Iter<'a, D>;
+
+    fn into_iter(self) -> Iter<'a, D> {
+        self.iter()
+    }
+}
+
+impl<D: FromPoints> Clone for Stabby<D> {
+    fn clone(&self) -> Self {
+        // Not every domain can be cloned, but every one can be rebuilt from
+        // its points.
+        let repr = match &self.repr {
+            Repr::Small(xs) => Repr::Small(xs.clone()),
+            Repr::Full { domain, dense, len } => Repr::Full {
+                domain: D::from_points((0..domain.count()).map(|i| domain.select(i)).collect()),
+                dense: dense.clone(),
+                len: *len,
+            },
+        };
+        Stabby { repr }
+    }
+}
+
+impl<D: DomainMap> Debug for Stabby<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Indexes are equal when they hold the same intervals, however they are
+/// represented.
+///
+impl<D: DomainMap> PartialEq for Stabby<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<D: DomainMap> Eq for Stabby<D> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::{
+        test_util::{intervals, many_intervals},
+        EytzingerDomain,
+    };
+
+    #[test]
+    fn test_iter() {
+        for xs in [Vec::new(), intervals(), many_intervals()] {
+            let s = Stabby::new(&xs);
+            assert_eq!(s.len(), xs.len());
+            assert_eq!(s.is_empty(), xs.is_empty());
+            assert_eq!(s.iter().len(), xs.len());
+            assert_eq!(s.iter().collect::<Vec<Interval>>(), xs);
+            assert_eq!((&s).into_iter().count(), xs.len());
+            let mut seen: Vec<Interval> = Vec::new();
+            for x in &s {
+                seen.push(x);
+            }
+            assert_eq!(seen, xs);
+        }
+    }
+
+    #[test]
+    fn test_starting_ending_in() {
+        for xs in [intervals(), many_intervals()] {
+            let s = Stabby::new(&xs);
+            for (lo, hi) in [
+                (0, 0),
+                (0, 5),
+                (10, 10),
+                (20, 45),
+                (55, 230),
+                (150, 600),
+                (499, 538),
+                (1000, u64::MAX),
+            ] {
+                let want: Vec<Interval> = xs
+                    .iter()
+                    .filter(|x| lo <= x.first && x.first <= hi)
+                    .copied()
+                    .collect();
+                assert_eq!(s.starting_in(lo..=hi), want);
+                let want: Vec<Interval> = xs
+                    .iter()
+                    .filter(|x| lo <= x.last && x.last <= hi)
+                    .copied()
+                    .collect();
+                assert_eq!(s.ending_in(lo..=hi), want);
+            }
+            assert_eq!(s.starting_in(..), xs);
+            assert_eq!(s.ending_in(300..), s.ending_in(300..=u64::MAX));
+            assert!(s.starting_in(40..40).is_empty());
+        }
+        let s = Stabby::new(&intervals());
+        assert_eq!(
+            s.starting_in(20..40),
+            vec![Interval::new(20, 30), Interval::new(25, 150)]
+        );
+        assert_eq!(
+            s.ending_in(..=50),
+            vec![Interval::new(20, 30), Interval::new(40, 50)]
+        );
+    }
+
+    #[test]
+    fn test_clone_debug_eq() {
+        let s = Stabby::new(&many_intervals());
+        let t = s.clone();
+        assert_eq!(s, t);
+        assert_eq!(s.parts(), t.parts());
+        let e: Stabby<EytzingerDomain> = Stabby::with_domain(&many_intervals());
+        assert_eq!(e.clone(), e);
+        assert_ne!(s, Stabby::new(&intervals()));
+
+        let small = Stabby::new(&intervals()[..2]);
+        assert_eq!(
+            format!("{:?}", small),
+            "[Interval { first: 10, last: 100 }, Interval { first: 10, last: 200 }]"
+        );
+    }
+}
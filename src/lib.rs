@@ -1,4 +1,5 @@
 #![warn(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! The `stabby` crate provides an implementation of the data structure
 //! and associated algorithms presented in:
@@ -41,10 +42,79 @@
 //!            vec![Interval::new(45_331_182, 45_331_334),
 //!                 Interval::new(45_331_420, 45_331_556)]);
 //! ```
+//!
+//! # Features
+//!
+//! * `std` (enabled by default): the binary format and reading and writing
+//!   it (`format`), memory-mapped indexes (`MappedStabby`) and
+//!   `SharedStabby`. Without it, the crate is `no_std` and needs only
+//!   `alloc`, keeping its tables in B-trees rather than hash tables, and
+//!   its domain in a plain `Vec` rather than `ransel`'s sorted vector.
+//! * `serde`: implement `Serialize` and `Deserialize` for [`Interval`] and
+//!   [`Stabby`].
+//! * `rayon`: sort and rank endpoints in parallel while building a [`Stabby`],
+//!   and build indexes for many groups of intervals at once with
+//!   `Stabby::par_new_many`. The indexes built are identical to those
+//!   built sequentially. Batches of queries may also be answered in
+//!   parallel, with `Stabby::par_stab_many` and
+//!   `Stabby::par_stab_interval_many`. Implies `std`.
 
+extern crate alloc;
+
+mod collections;
 mod listy;
 mod dense;
 mod sparse;
+mod domain;
+mod error;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+mod mapped;
+mod dynamic;
+mod append;
+mod window;
+mod merge;
+mod slice;
+mod static_stabby;
+mod index;
+mod nclist;
+mod interval_tree;
+mod stats;
+mod iter;
+mod exact;
+mod fuzzy;
+mod interval;
+mod coords;
+mod stranded;
+mod region;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "rayon")]
+mod parallel;
 
 pub use sparse::Interval;
 pub use sparse::Stabby;
+pub use dense::{DenseInterval, DenseStabby};
+pub use domain::{DomainMap, EytzingerDomain, FromPoints, IdentityDomain, MaybeSync};
+pub use error::StabbyError;
+#[cfg(feature = "std")]
+pub use mapped::{MappedStabby, MappedStabbySet};
+pub use dynamic::DynamicStabby;
+pub use append::AppendStabby;
+pub use window::WindowStabby;
+pub use static_stabby::StaticStabby;
+pub use index::{NaiveIndex, StabbingIndex};
+pub use nclist::NcList;
+pub use interval_tree::IntervalTree;
+pub use stats::{HeapSize, LengthStats, StabbyStats};
+pub use iter::Iter;
+pub use coords::{Coordinates, OneClosed, ZeroHalfOpen};
+pub use stranded::{Strand, StrandMode, StrandedStabby};
+pub use region::Region;
+#[cfg(feature = "std")]
+pub use shared::SharedStabby;
@@ -1,10 +1,11 @@
 
-use std::{
-    collections::HashMap,
+use core::{
     fmt::{self, Debug, Display},
     marker::PhantomData,
 };
 
+use crate::collections::Map;
+
 #[derive(Clone, Copy, Eq, Default, Hash, PartialEq, Debug)]
 pub struct ListyElement<T>(u64, PhantomData<T>);
 
@@ -17,30 +18,29 @@ impl<T> Display for ListyElement<T> {
 /// A pointer-ful doubly linked list implementation that allows
 /// nodes to be referenced by a "pointer" that can be used to
 /// delete them from the interior of the list.
- 
 pub struct Listy<T>
 where
-    T: std::fmt::Debug,
+    T: core::fmt::Debug,
 {
     counter: u64,
-    items: HashMap<u64, T>,
-    pred: HashMap<u64, u64>,
-    succ: HashMap<u64, u64>,
+    items: Map<u64, T>,
+    pred: Map<u64, u64>,
+    succ: Map<u64, u64>,
     first: Option<u64>,
     last: Option<u64>,
 }
 
 impl<T> Listy<T>
 where
-    T: std::fmt::Debug,
+    T: core::fmt::Debug,
 {
     /// Create an empty list
     pub fn new() -> Listy<T> {
         Listy {
             counter: 0,
-            items: HashMap::new(),
-            pred: HashMap::new(),
-            succ: HashMap::new(),
+            items: Map::new(),
+            pred: Map::new(),
+            succ: Map::new(),
             first: None,
             last: None,
         }
@@ -62,10 +62,7 @@ where
 
     #[allow(dead_code)]
     pub fn front_ptr(&self) -> Option<ListyElement<T>> {
-        match self.first {
-            None => None,
-            Some(x) => Some(ListyElement(x, PhantomData)),
-        }
+        self.first.map(|x| ListyElement(x, PhantomData))
     }
 
     pub fn back(&self) -> Option<&T> {
@@ -77,10 +74,7 @@ where
 
     #[allow(dead_code)]
     pub fn back_ptr(&self) -> Option<ListyElement<T>> {
-        match self.last {
-            None => None,
-            Some(x) => Some(ListyElement(x, PhantomData)),
-        }
+        self.last.map(|x| ListyElement(x, PhantomData))
     }
 
     #[allow(dead_code)]
@@ -229,6 +223,7 @@ where
         res
     }
 
+    #[cfg(feature = "std")]
     #[allow(dead_code)]
     fn dump(&self) {
         println!("ends: {}, {}", self.first.unwrap_or(999), self.last.unwrap_or(999));
@@ -0,0 +1,323 @@
+use std::{
+    fs::File,
+    ops::Range,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+
+use memmap2::Mmap;
+
+use crate::{
+    format::{parse_set, Layout, RawIndex},
+    sparse::{stab_interval_with, stab_with, stabs_with},
+    Interval, Region, Stabby, StabbyError,
+};
+
+/// Map a file read-only.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it remains mapped.
+///
+unsafe fn map_file(path: &Path) -> Result<Arc<Mmap>, StabbyError> {
+    let file = File::open(path)?;
+    // SAFETY: passed on to the caller.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(Arc::new(map))
+}
+
+/// A read-only index that answers queries directly from a memory-mapped
+/// file written by [`Stabby::write_to`], without copying it into memory.
+///
+/// Lookups that the in-memory [`Stabby`] makes through hash tables are made
+/// by binary search over the mapped tables instead, so queries are somewhat
+/// slower. Opening an index reads only its header, and the pages holding
+/// the rest are read as queries touch them.
+///
+/// Because opening does not read the payload, it cannot detect damage to
+/// it. Queries on a damaged file may give wrong answers, panic, or fail to
+/// finish. Call [`verify`](MappedStabby::verify) to read and check the
+/// whole index, at a cost proportional to its size, where the file may not
+/// be trusted.
+///
+/// `MappedStabby` is cheap to clone: clones share the same mapping.
+///
+#[derive(Clone)]
+pub struct MappedStabby {
+    map: Arc<Mmap>,
+    range: Range<usize>,
+    layout: Layout,
+}
+
+impl MappedStabby {
+    /// Map a file holding a single index, checking only its header.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it remains mapped,
+    /// by this process or any other. Doing so is undefined behaviour, as
+    /// with [`Mmap::map`].
+    ///
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<MappedStabby, StabbyError> {
+        // SAFETY: passed on to the caller.
+        let map = unsafe { map_file(path.as_ref())? };
+        let range = 0..map.len();
+        MappedStabby::new(map, range)
+    }
+
+    fn new(map: Arc<Mmap>, range: Range<usize>) -> Result<MappedStabby, StabbyError> {
+        let layout = RawIndex::open(&map[range.clone()])?.layout();
+        Ok(MappedStabby { map, range, layout })
+    }
+
+    /// Read the whole index, and check its checksum, its structure, and
+    /// that its intervals match its fingerprint.
+    ///
+    pub fn verify(&self) -> Result<(), StabbyError> {
+        let raw = self.raw();
+        raw.verify()?;
+        raw.check_fingerprint()
+    }
+
+    fn raw(&self) -> RawIndex<'_> {
+        RawIndex::with_layout(self.layout, &self.map[self.range.clone()])
+    }
+
+    /// Test if a position stabs any intervals.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        let raw = self.raw();
+        stabs_with(&raw, &raw, q)
+    }
+
+    /// Retrieve the list of intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        let raw = self.raw();
+        stab_with(&raw, &raw, q)
+    }
+
+    /// Find the list of intervals that intersect the given query interval.
+    /// The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        let raw = self.raw();
+        stab_interval_with(&raw, &raw, q)
+    }
+
+    /// The fingerprint of the intervals in the index, as returned by
+    /// [`Stabby::fingerprint`].
+    ///
+    pub fn fingerprint(&self) -> u64 {
+        self.layout.header.fingerprint
+    }
+
+    /// Copy the index into memory as an ordinary [`Stabby`].
+    ///
+    pub fn load(&self) -> Stabby {
+        self.raw().to_stabby()
+    }
+}
+
+struct MappedEntry {
+    name: String,
+    range: Range<usize>,
+    layout: OnceLock<Layout>,
+}
+
+/// A memory-mapped file holding a set of named indexes, as written by
+/// [`write_indexes`](crate::format::write_indexes).
+///
+/// Opening the set reads only its table of contents, and the header of
+/// each index is read the first time it is requested with
+/// [`get`](MappedStabbySet::get), so the cost of opening a file with many
+/// indexes, for example one per contig, is borne only for the indexes
+/// actually used. As with [`MappedStabby`], the indexes are not otherwise
+/// checked unless [verified](MappedStabby::verify).
+///
+pub struct MappedStabbySet {
+    map: Arc<Mmap>,
+    entries: Vec<MappedEntry>,
+}
+
+impl MappedStabbySet {
+    /// Map a file holding a set of indexes and read its table of contents.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it remains mapped,
+    /// by this process or any other, including by way of the indexes
+    /// retrieved from the set.
+    ///
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<MappedStabbySet, StabbyError> {
+        // SAFETY: passed on to the caller.
+        let map = unsafe { map_file(path.as_ref())? };
+        let entries = parse_set(&map)?
+            .into_iter()
+            .map(|e| MappedEntry {
+                name: e.name,
+                range: e.range,
+                layout: OnceLock::new(),
+            })
+            .collect();
+        Ok(MappedStabbySet { map, entries })
+    }
+
+    /// The number of indexes in the set.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Test whether the set holds no indexes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The names of the indexes in the set, in sorted order.
+    ///
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    /// Retrieve the index with the given name, checking its header if this
+    /// is the first time it has been requested. Returns `Ok(None)` if the set
+    /// has no index of that name.
+    ///
+    pub fn get(&self, name: &str) -> Result<Option<MappedStabby>, StabbyError> {
+        let i = match self.entries.binary_search_by(|e| e.name.as_str().cmp(name)) {
+            Ok(i) => i,
+            Err(_) => return Ok(None),
+        };
+        let e = &self.entries[i];
+        let m = match e.layout.get() {
+            Some(layout) => MappedStabby {
+                map: self.map.clone(),
+                range: e.range.clone(),
+                layout: *layout,
+            },
+            None => {
+                let m = MappedStabby::new(self.map.clone(), e.range.clone())?;
+                let _ = e.layout.set(m.layout);
+                m
+            }
+        };
+        Ok(Some(m))
+    }
+
+    /// Find the intervals that intersect a region, from the index named
+    /// by its contig, or all of that index's intervals if the region is a
+    /// whole contig. Returns `Ok(None)` if the set has no such index.
+    ///
+    pub fn stab_region(&self, region: &Region) -> Result<Option<Vec<Interval>>, StabbyError> {
+        Ok(self
+            .get(&region.contig)?
+            .map(|m| m.stab_interval(&region.to_interval())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        format::write_indexes,
+        test_util::{intervals, many_intervals},
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("stabby-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_mapped_1() {
+        for (name, xs) in [("mapped-1", intervals()), ("mapped-many", many_intervals())] {
+            let s = Stabby::new(&xs);
+            let path = temp_path(name);
+            s.write_to(&mut File::create(&path).unwrap()).unwrap();
+
+            // SAFETY: the file is private to this test, and not modified.
+            let m = unsafe { MappedStabby::open(&path) }.unwrap();
+            m.verify().unwrap();
+            assert_eq!(m.fingerprint(), s.fingerprint());
+            for q in 0..600 {
+                assert_eq!(m.stabs(q), s.stabs(q));
+                assert_eq!(m.stab(q), s.stab(q));
+                let qi = Interval::new(q, q + 23);
+                assert_eq!(m.stab_interval(&qi), s.stab_interval(&qi));
+            }
+            assert_eq!(m.load().stab(45), s.stab(45));
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mapped_corrupt() {
+        let mut buf: Vec<u8> = Vec::new();
+        Stabby::new(&many_intervals()).write_to(&mut buf).unwrap();
+        let n = buf.len();
+        buf[n - 1] ^= 1;
+        let path = temp_path("mapped-corrupt");
+        std::fs::write(&path, &buf).unwrap();
+        // SAFETY: the file is private to this test, and not modified.
+        let m = unsafe { MappedStabby::open(&path) }.unwrap();
+        let res = m.verify();
+        assert!(matches!(res, Err(StabbyError::ChecksumMismatch { .. })));
+
+        // A damaged header is detected on opening.
+        std::fs::write(&path, &buf[..n - 8]).unwrap();
+        // SAFETY: as above.
+        let res = unsafe { MappedStabby::open(&path) };
+        assert!(matches!(res, Err(StabbyError::Truncated)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_set() {
+        let a = Stabby::new(&many_intervals());
+        let b = Stabby::new(&[Interval::new(5, 7), Interval::new(6, 9)]);
+        let path = temp_path("mapped-set");
+        let mut f = File::create(&path).unwrap();
+        write_indexes(&mut f, [("chr2", &b), ("chr1", &a)]).unwrap();
+        drop(f);
+
+        // SAFETY: the file is private to this test, and not modified.
+        let set = unsafe { MappedStabbySet::open(&path) }.unwrap();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.names().collect::<Vec<&str>>(), vec!["chr1", "chr2"]);
+        assert!(set.get("chrX").unwrap().is_none());
+        let m1 = set.get("chr1").unwrap().unwrap();
+        let m2 = set.get("chr2").unwrap().unwrap();
+        m1.verify().unwrap();
+        m2.verify().unwrap();
+        for q in 0..600 {
+            assert_eq!(m1.stab(q), a.stab(q));
+            assert_eq!(m2.stab(q), b.stab(q));
+        }
+        assert_eq!(set.get("chr1").unwrap().unwrap().stab(45), a.stab(45));
+
+        let r: Region = "chr1:1,000-".parse().unwrap();
+        assert_eq!(set.stab_region(&r).unwrap(), Some(vec![]));
+        let r: Region = "chr1:46-55".parse().unwrap();
+        assert_eq!(
+            set.stab_region(&r).unwrap(),
+            Some(a.stab_interval(&Interval::new(45, 54)))
+        );
+        let r: Region = "chr2".parse().unwrap();
+        assert_eq!(set.stab_region(&r).unwrap(), Some(b.intervals()));
+        assert_eq!(set.stab_region(&"chrX:5".parse().unwrap()).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let a = Stabby::new(&intervals());
+        let mut buf: Vec<u8> = Vec::new();
+        let res = write_indexes(&mut buf, [("chr1", &a), ("chr1", &a)]);
+        assert!(matches!(res, Err(StabbyError::DuplicateName(_))));
+    }
+}
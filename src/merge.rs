@@ -0,0 +1,162 @@
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::{
+    dense::DenseInterval,
+    domain::{Domain, FromPoints},
+    Stabby,
+};
+
+/// Merge two sorted lists, dropping duplicates between them.
+fn merge_pair<T: Ord + Copy>(xs: &[T], ys: &[T]) -> Vec<T> {
+    let mut res: Vec<T> = Vec::with_capacity(xs.len() + ys.len());
+    let (mut i, mut j) = (0, 0);
+    while i < xs.len() && j < ys.len() {
+        match xs[i].cmp(&ys[j]) {
+            Ordering::Less => {
+                res.push(xs[i]);
+                i += 1;
+            }
+            Ordering::Greater => {
+                res.push(ys[j]);
+                j += 1;
+            }
+            Ordering::Equal => {
+                res.push(xs[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    res.extend_from_slice(&xs[i..]);
+    res.extend_from_slice(&ys[j..]);
+    res
+}
+
+/// Merge any number of sorted lists of distinct items, pairing them off
+/// in rounds so that each item is copied O(log k) times.
+fn merge_lists<T: Ord + Copy>(mut lists: Vec<Vec<T>>) -> Vec<T> {
+    while lists.len() > 1 {
+        let mut next: Vec<Vec<T>> = Vec::with_capacity(lists.len().div_ceil(2));
+        let mut it = lists.into_iter();
+        while let Some(xs) = it.next() {
+            match it.next() {
+                Some(ys) => next.push(merge_pair(&xs, &ys)),
+                None => next.push(xs),
+            }
+        }
+        lists = next;
+    }
+    lists.pop().unwrap_or_default()
+}
+
+/// For each of a sorted list of points, its rank in a sorted superset.
+fn rerank(points: &[u64], all: &[u64]) -> Vec<usize> {
+    let mut res: Vec<usize> = Vec::with_capacity(points.len());
+    let mut j = 0;
+    for p in points.iter() {
+        while all[j] < *p {
+            j += 1;
+        }
+        res.push(j);
+    }
+    res
+}
+
+impl Stabby {
+    /// Build an index over the union of the intervals of two indexes.
+    ///
+    /// Since the domains and intervals of both are already sorted, they
+    /// are combined with a linear merge rather than sorted and ranked
+    /// afresh. The nesting structure is still rebuilt from scratch, which
+    /// is most of the cost of [`Stabby::new`], so the saving is modest:
+    /// `cargo bench --bench merge` times a merge of two indexes of half a
+    /// million random intervals each at around 90% of the time taken to
+    /// collect their intervals and build a new index.
+    ///
+    pub fn merge(a: &Stabby, b: &Stabby) -> Stabby {
+        Stabby::merge_all([a, b])
+    }
+
+    /// Build an index over the union of the intervals of any number of
+    /// indexes, as with [`Stabby::merge`].
+    ///
+    pub fn merge_all<'a, I: IntoIterator<Item = &'a Stabby>>(xs: I) -> Stabby {
+        let parts: Vec<(Vec<u64>, Vec<DenseInterval>)> =
+            xs.into_iter().map(|x| x.parts()).collect();
+        let all = merge_lists(parts.iter().map(|(points, _)| points.clone()).collect());
+        let lists: Vec<Vec<DenseInterval>> = parts
+            .iter()
+            .map(|(points, ys)| {
+                let ranks = rerank(points, &all);
+                ys.iter()
+                    .map(|y| DenseInterval::new(ranks[y.first / 2] * 2, ranks[y.last / 2] * 2))
+                    .collect()
+            })
+            .collect();
+        let ys = merge_lists(lists);
+        let all = if all.is_empty() { vec![0] } else { all };
+        Stabby::from_dense(Domain::from_points(all), &ys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Interval;
+
+    use super::*;
+    use crate::test_util::MiniRng;
+
+    fn random_intervals(rng: &mut MiniRng, n: usize) -> Vec<Interval> {
+        let mut xs: Vec<Interval> = (0..n)
+            .map(|_| {
+                let first = rng.rnd() % 1000;
+                Interval::new(first, first + rng.rnd() % 60)
+            })
+            .collect();
+        xs.sort();
+        xs.dedup();
+        xs
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut rng = MiniRng::new(8);
+        let xs = random_intervals(&mut rng, 300);
+        let mut ys = random_intervals(&mut rng, 200);
+        ys.extend(xs.iter().take(50));
+        ys.sort();
+        ys.dedup();
+        let mut zs = xs.clone();
+        zs.extend(ys.iter());
+        zs.sort();
+        zs.dedup();
+
+        let m = Stabby::merge(&Stabby::new(&xs), &Stabby::new(&ys));
+        let s = Stabby::new(&zs);
+        assert_eq!(m.parts(), s.parts());
+        for q in 0..1100 {
+            assert_eq!(m.stab(q), s.stab(q));
+        }
+    }
+
+    #[test]
+    fn test_merge_all() {
+        let mut rng = MiniRng::new(13);
+        let parts: Vec<Vec<Interval>> = (0..5).map(|_| random_intervals(&mut rng, 100)).collect();
+        let mut zs: Vec<Interval> = parts.iter().flatten().copied().collect();
+        zs.sort();
+        zs.dedup();
+
+        let indexes: Vec<Stabby> = parts.iter().map(|xs| Stabby::new(xs)).collect();
+        let m = Stabby::merge_all(indexes.iter());
+        assert_eq!(m.parts(), Stabby::new(&zs).parts());
+
+        let empty = Stabby::new(&[]);
+        assert_eq!(
+            Stabby::merge(&empty, &indexes[0]).parts(),
+            indexes[0].parts()
+        );
+        assert_eq!(Stabby::merge_all([]).parts(), empty.parts());
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::cmp::Reverse;
+
+use crate::{index::StabbingIndex, Interval};
+
+/// A range of positions in the layout of an [`NcList`].
+type Run = (usize, usize);
+
+/// A nested containment list, after:
+///
+/// > Alekseyenko, Alexander V., and Christopher J. Lee. "Nested Containment
+/// > List (NCList): a new algorithm for accelerating interval query of
+/// > genome alignment and interval databases." Bioinformatics 23, no. 11
+/// > (2007): 1386-1393.
+///
+/// Each interval is placed in the list of the smallest interval that
+/// contains it, if any. Within a list, no interval contains another, so
+/// the lists are sorted by both start and end, and the intervals
+/// overlapping a query form a contiguous run, found by binary search.
+/// Each list is laid out contiguously, so queries touch little memory.
+///
+#[derive(Clone, Debug, Default)]
+pub struct NcList {
+    items: Vec<Interval>,
+    sublists: Vec<Run>,
+    top: Run,
+}
+
+impl NcList {
+    /// Visit the intervals intersecting the query, list by list.
+    fn visit<F: FnMut(&Interval)>(&self, q: &Interval, mut f: F) {
+        let mut todo: Vec<Run> = vec![self.top];
+        while let Some((s, e)) = todo.pop() {
+            let run = &self.items[s..e];
+            let mut i = run.partition_point(|x| x.last < q.first);
+            while i < run.len() && run[i].first <= q.last {
+                f(&run[i]);
+                let sub = self.sublists[s + i];
+                if sub.0 < sub.1 {
+                    todo.push(sub);
+                }
+                i += 1;
+            }
+        }
+    }
+}
+
+impl StabbingIndex for NcList {
+    fn build(xs: &[Interval]) -> Self {
+        let mut xs: Vec<Interval> = xs.to_vec();
+        xs.sort_unstable_by_key(|x| (x.first, Reverse(x.last)));
+
+        // Find the children of each interval, with a stack of the
+        // intervals containing the current one.
+        let mut roots: Vec<usize> = Vec::new();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); xs.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, x) in xs.iter().enumerate() {
+            while let Some(&p) = stack.last() {
+                if x.last <= xs[p].last {
+                    break;
+                }
+                stack.pop();
+            }
+            match stack.last() {
+                Some(&p) => children[p].push(i),
+                None => roots.push(i),
+            }
+            stack.push(i);
+        }
+
+        // Lay the lists out breadth first.
+        let mut items: Vec<Interval> = Vec::with_capacity(xs.len());
+        let mut sublists: Vec<Run> = vec![(0, 0); xs.len()];
+        let mut top: Run = (0, 0);
+        let mut queue: VecDeque<(Vec<usize>, Option<usize>)> = VecDeque::new();
+        queue.push_back((roots, None));
+        while let Some((list, parent)) = queue.pop_front() {
+            let start = items.len();
+            for i in list.iter() {
+                items.push(xs[*i]);
+            }
+            let run = (start, items.len());
+            match parent {
+                Some(p) => sublists[p] = run,
+                None => top = run,
+            }
+            for (j, i) in list.iter().enumerate() {
+                let kids = core::mem::take(&mut children[*i]);
+                if !kids.is_empty() {
+                    queue.push_back((kids, Some(start + j)));
+                }
+            }
+        }
+
+        NcList {
+            items,
+            sublists,
+            top,
+        }
+    }
+
+    fn stabs(&self, q: u64) -> bool {
+        // An interval containing the position contains one in the top list.
+        let run = &self.items[self.top.0..self.top.1];
+        let i = run.partition_point(|x| x.last < q);
+        i < run.len() && run[i].first <= q
+    }
+
+    fn stab(&self, q: u64) -> Vec<Interval> {
+        self.stab_interval(&Interval::new(q, q))
+    }
+
+    fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        let mut res: Vec<Interval> = Vec::new();
+        self.visit(q, |x| res.push(*x));
+        res.sort_unstable();
+        res
+    }
+
+    fn count(&self, q: u64) -> usize {
+        self.count_interval(&Interval::new(q, q))
+    }
+
+    fn count_interval(&self, q: &Interval) -> usize {
+        let mut n = 0;
+        self.visit(q, |_| n += 1);
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nesting() {
+        let xs = vec![
+            Interval::new(10, 100),
+            Interval::new(10, 200),
+            Interval::new(20, 30),
+            Interval::new(25, 150),
+            Interval::new(40, 50),
+            Interval::new(210, 220),
+        ];
+        let idx = NcList::build(&xs);
+        assert_eq!(idx.top, (0, 2));
+        assert_eq!(
+            idx.items[..2],
+            [Interval::new(10, 200), Interval::new(210, 220)]
+        );
+        assert_eq!(
+            idx.stab(45),
+            vec![
+                Interval::new(10, 100),
+                Interval::new(10, 200),
+                Interval::new(25, 150),
+                Interval::new(40, 50),
+            ]
+        );
+        assert_eq!(idx.count(205), 0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Parallel construction and querying, enabled with the `rayon` feature.
+
+use rayon::prelude::*;
+
+use crate::{Interval, Stabby};
+
+/// The smallest number of queries handed to a thread at once. Individual
+/// queries are cheap, so smaller shards cost more in scheduling than they
+/// gain in balance.
+const MIN_SHARD: usize = 256;
+
+impl Stabby {
+    /// Build an index for each of several groups of intervals, such as the
+    /// intervals on each contig, in parallel. Each group must satisfy the
+    /// requirements of [`Stabby::new`].
+    ///
+    /// The indexes are returned in the same order as the groups, and each
+    /// is identical to the one [`Stabby::new`] would build.
+    ///
+    pub fn par_new_many<T: AsRef<[Interval]> + Sync>(groups: &[T]) -> Vec<Stabby> {
+        groups.par_iter().map(|g| Stabby::new(g.as_ref())).collect()
+    }
+
+    /// Stab each of a batch of positions, sharing the work across threads.
+    /// The result for each position is as returned by [`Stabby::stab`],
+    /// and the results are in the same order as the positions.
+    ///
+    pub fn par_stab_many(&self, qs: &[u64]) -> Vec<Vec<Interval>> {
+        qs.par_iter()
+            .with_min_len(MIN_SHARD)
+            .map(|q| self.stab(*q))
+            .collect()
+    }
+
+    /// Find the intervals intersecting each of a batch of query intervals,
+    /// sharing the work across threads. The result for each query is as
+    /// returned by [`Stabby::stab_interval`], and the results are in the
+    /// same order as the queries.
+    ///
+    pub fn par_stab_interval_many(&self, qs: &[Interval]) -> Vec<Vec<Interval>> {
+        qs.par_iter()
+            .with_min_len(MIN_SHARD)
+            .map(|q| self.stab_interval(q))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MiniRng;
+
+    fn random_intervals(seed: u64, n: usize) -> Vec<Interval> {
+        let mut rng = MiniRng::new(seed);
+        let mut xs: Vec<Interval> = Vec::new();
+        for _i in 0..n {
+            let first = rng.rnd() % 100_000;
+            let last = first + rng.rnd() % 500;
+            xs.push(Interval::new(first, last));
+        }
+        xs.sort();
+        xs.dedup();
+        xs
+    }
+
+    fn bytes(s: &Stabby) -> Vec<u8> {
+        let mut buf: Vec<u8> = Vec::new();
+        s.write_to(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_par_new_many() {
+        let groups: Vec<Vec<Interval>> = (0..8).map(|i| random_intervals(i + 1, 5000)).collect();
+        let built = Stabby::par_new_many(&groups);
+        assert_eq!(built.len(), groups.len());
+        for (g, s) in groups.iter().zip(built.iter()) {
+            let t = Stabby::new(g);
+            assert_eq!(bytes(s), bytes(&t));
+            for q in (0..100_000).step_by(97) {
+                assert_eq!(s.stab(q), t.stab(q));
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_stab_many() {
+        let s = Stabby::new(&random_intervals(42, 20_000));
+        let mut rng = MiniRng::new(19);
+        let qs: Vec<u64> = (0..10_000).map(|_| rng.rnd() % 100_500).collect();
+        let res = s.par_stab_many(&qs);
+        assert_eq!(res.len(), qs.len());
+        for (q, r) in qs.iter().zip(res.iter()) {
+            assert_eq!(*r, s.stab(*q));
+        }
+    }
+
+    #[test]
+    fn test_par_stab_interval_many() {
+        let s = Stabby::new(&random_intervals(23, 20_000));
+        let mut rng = MiniRng::new(7);
+        let qs: Vec<Interval> = (0..5_000)
+            .map(|_| {
+                let first = rng.rnd() % 100_500;
+                Interval::new(first, first + rng.rnd() % 1000)
+            })
+            .collect();
+        let res = s.par_stab_interval_many(&qs);
+        assert_eq!(res.len(), qs.len());
+        for (q, r) in qs.iter().zip(res.iter()) {
+            assert_eq!(*r, s.stab_interval(q));
+        }
+    }
+}
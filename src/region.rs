@@ -0,0 +1,368 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{fmt::Display, str::FromStr};
+
+use crate::{Interval, OneClosed, StabbyError};
+
+use sealed::Bounds;
+
+mod sealed {
+    use crate::{Coordinates, Interval, OneClosed, ZeroHalfOpen};
+
+    /// The start and end of an interval as written, for the conventions
+    /// a region may be written in.
+    pub trait Bounds: Coordinates {
+        /// Make an interval from its start and end, without checking them.
+        fn from_bounds(start: u64, end: u64) -> Self;
+
+        /// The start and end of the interval.
+        fn bounds(&self) -> (u64, u64);
+    }
+
+    impl Bounds for Interval {
+        fn from_bounds(start: u64, end: u64) -> Self {
+            Interval {
+                first: start,
+                last: end,
+            }
+        }
+
+        fn bounds(&self) -> (u64, u64) {
+            (self.first, self.last)
+        }
+    }
+
+    impl Bounds for ZeroHalfOpen {
+        fn from_bounds(start: u64, end: u64) -> Self {
+            ZeroHalfOpen { start, end }
+        }
+
+        fn bounds(&self) -> (u64, u64) {
+            (self.start, self.end)
+        }
+    }
+
+    impl Bounds for OneClosed {
+        fn from_bounds(start: u64, end: u64) -> Self {
+            OneClosed { start, end }
+        }
+
+        fn bounds(&self) -> (u64, u64) {
+            (self.start, self.end)
+        }
+    }
+}
+
+/// A region of a named sequence, as typed by users of genome browsers and
+/// `samtools`: `chr1:45,329,163-45,340,447` for a range, `chrX:100` for a
+/// single position, `chr1:100-` for everything from a position on, or
+/// `chrM` for a whole contig.
+///
+/// Commas in positions are ignored. Regions are parsed with [`FromStr`] as
+/// 1-based and closed, as `samtools` does, or in any other convention with
+/// [`parse_as`](Region::parse_as). Either way, the range is held as a
+/// closed, 0-based [`Interval`], and `Display` writes it back 1-based.
+///
+/// A contig whose name contains a `:` may be written in braces, as in
+/// `{HLA-A*01:01}:100-200`, and is written that way by `Display`. Without
+/// braces, text after the last `:` that looks like a range is read as one,
+/// except that a single position with a leading zero, as in `HLA-A*01:01`,
+/// is rejected as ambiguous.
+///
+/// ```rust
+/// use stabby::{Interval, Region, ZeroHalfOpen};
+///
+/// let r: Region = "chr1:45,329,163-45,340,447".parse().unwrap();
+/// assert_eq!(r.contig, "chr1");
+/// assert_eq!(r.interval, Some(Interval::new(45_329_162, 45_340_446)));
+/// assert_eq!(r.to_string(), "chr1:45329163-45340447");
+///
+/// let bed = Region::parse_as::<ZeroHalfOpen>("chr1:45329162-45340447").unwrap();
+/// assert_eq!(bed, r);
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Region {
+    /// The name of the sequence.
+    pub contig: String,
+
+    /// The positions of the region, or `None` for the whole sequence.
+    pub interval: Option<Interval>,
+}
+
+impl Region {
+    /// Create a region covering an interval of a sequence.
+    ///
+    pub fn new<S: Into<String>>(contig: S, interval: Interval) -> Region {
+        Region {
+            contig: contig.into(),
+            interval: Some(interval),
+        }
+    }
+
+    /// Create a region covering the whole of a sequence.
+    ///
+    pub fn whole<S: Into<String>>(contig: S) -> Region {
+        Region {
+            contig: contig.into(),
+            interval: None,
+        }
+    }
+
+    /// The positions of the region as an interval to query with, which for
+    /// a whole sequence is every position.
+    ///
+    pub fn to_interval(&self) -> Interval {
+        self.interval.unwrap_or(Interval::new(0, u64::MAX))
+    }
+
+    /// Parse a region whose positions are in the given coordinate system,
+    /// one of [`Interval`], [`ZeroHalfOpen`](crate::ZeroHalfOpen) and
+    /// [`OneClosed`].
+    ///
+    /// A single position, or the start of a range open at its end, is
+    /// converted as a position of that system, so that `chr1:100` is the
+    /// 101st base with [`ZeroHalfOpen`](crate::ZeroHalfOpen), but the 100th
+    /// with [`OneClosed`].
+    ///
+    pub fn parse_as<C: Bounds>(s: &str) -> Result<Region, StabbyError> {
+        let err = |reason| StabbyError::Parse {
+            input: s.to_string(),
+            reason,
+        };
+        let t = s.trim();
+        let (contig, range) = match t.strip_prefix('{') {
+            Some(rest) => {
+                let (contig, rest) = rest
+                    .split_once('}')
+                    .ok_or_else(|| err("unclosed brace in contig name"))?;
+                match rest {
+                    "" => (contig, None),
+                    _ => {
+                        let range = rest
+                            .strip_prefix(':')
+                            .ok_or_else(|| err("expected ':' after contig name"))?;
+                        (contig, Some(range))
+                    }
+                }
+            }
+            // Contig names may themselves hold a ':', so only a suffix that
+            // looks like a range is taken as one.
+            None => match t.rsplit_once(':') {
+                Some((_, range)) if is_range(range) && is_padded(range) => {
+                    return Err(err("ambiguous contig name; write it in braces"));
+                }
+                Some((contig, range)) if is_range(range) => (contig, Some(range)),
+                _ => (t, None),
+            },
+        };
+        if contig.is_empty() {
+            return Err(err("empty contig name"));
+        }
+        let interval = match range {
+            None => None,
+            Some(range) => {
+                let (start, end) = match range.split_once('-') {
+                    None => (range, None),
+                    Some((start, end)) => (start, Some(end)),
+                };
+                let start = parse_position(start).ok_or_else(|| err("invalid start position"))?;
+                let x = match end {
+                    None => {
+                        let p = C::to_position(start)?;
+                        Interval::new(p, p)
+                    }
+                    Some("") => Interval::new(C::to_position(start)?, u64::MAX),
+                    Some(end) => {
+                        let end = parse_position(end).ok_or_else(|| err("invalid end position"))?;
+                        C::from_bounds(start, end).to_interval()?
+                    }
+                };
+                Some(x)
+            }
+        };
+        Ok(Region {
+            contig: contig.to_string(),
+            interval,
+        })
+    }
+
+    /// Write the region with its positions in the given coordinate system,
+    /// one of those [`parse_as`](Region::parse_as) accepts, in a form it
+    /// reads back.
+    ///
+    /// Fails only where the region has no equivalent in that system.
+    ///
+    pub fn to_string_as<C: Bounds>(&self) -> Result<String, StabbyError> {
+        let contig = if self.contig.contains(':') {
+            format!("{{{}}}", self.contig)
+        } else {
+            self.contig.clone()
+        };
+        let x = match self.interval {
+            None => return Ok(contig),
+            Some(x) => x,
+        };
+        let start = C::from_interval(&Interval::new(x.first, x.first))?
+            .bounds()
+            .0;
+        let s = if x.last == u64::MAX {
+            format!("{}:{}-", contig, start)
+        } else if x.first == x.last {
+            format!("{}:{}", contig, start)
+        } else {
+            let (start, end) = C::from_interval(&x)?.bounds();
+            format!("{}:{}-{}", contig, start, end)
+        };
+        Ok(s)
+    }
+}
+
+/// Parse a position, ignoring any commas that group its digits.
+fn parse_position(s: &str) -> Option<u64> {
+    let digits: String = s.chars().filter(|c| *c != ',').collect();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Test if the text after a contig name's last `:` is meant as a range.
+fn is_range(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_digit() || b == b',' || b == b'-')
+}
+
+/// Test if a range is a single position written with a leading zero, more
+/// likely the end of a contig name such as `HLA-A*01:01` than a position.
+fn is_padded(s: &str) -> bool {
+    !s.contains('-') && s.len() > 1 && s.starts_with('0')
+}
+
+impl FromStr for Region {
+    type Err = StabbyError;
+
+    fn from_str(s: &str) -> Result<Region, StabbyError> {
+        Region::parse_as::<OneClosed>(s)
+    }
+}
+
+/// Write the region 1-based and closed. A region starting at the last
+/// `u64` position has no such form, and fails to format.
+///
+impl Display for Region {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = self
+            .to_string_as::<OneClosed>()
+            .map_err(|_| core::fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::ZeroHalfOpen;
+
+    const MAX: u64 = u64::MAX;
+
+    #[test]
+    fn test_parse() {
+        let cases = [
+            (
+                "chr1:45,329,163-45,340,447",
+                "chr1",
+                Some((45_329_162, 45_340_446)),
+            ),
+            ("chrX:100", "chrX", Some((99, 99))),
+            (" chr2:1-1 ", "chr2", Some((0, 0))),
+            ("chr3:100-", "chr3", Some((99, MAX))),
+            ("chrM", "chrM", None),
+            ("{HLA-A*01:01}", "HLA-A*01:01", None),
+            ("{HLA-A*01:01}:5-6", "HLA-A*01:01", Some((4, 5))),
+            ("chrUn:KI270302v1", "chrUn:KI270302v1", None),
+        ];
+        for (s, contig, interval) in cases {
+            let r: Region = s.parse().unwrap();
+            assert_eq!(r.contig, contig, "{}", s);
+            assert_eq!(
+                r.interval,
+                interval.map(|(a, b)| Interval::new(a, b)),
+                "{}",
+                s
+            );
+        }
+        // An unbraced name ending in a padded number is ambiguous.
+        assert!(matches!(
+            "HLA-A*01:01".parse::<Region>(),
+            Err(StabbyError::Parse { .. })
+        ));
+        assert_eq!(
+            "HLA-A*01:01-1".parse::<Region>().unwrap(),
+            Region::new("HLA-A*01", Interval::new(0, 0))
+        );
+        assert_eq!(
+            Region::parse_as::<ZeroHalfOpen>("chr1:0").unwrap(),
+            Region::new("chr1", Interval::new(0, 0))
+        );
+
+        for bad in [
+            "",
+            ":100",
+            "chr1:0",
+            "chr1:0-10",
+            "chr1:20-10",
+            "chr1:-10",
+            "chr1:1-2-3",
+            "chr1:,",
+            "chr1:18446744073709551616",
+            "{chr1",
+            "{chr1}100",
+        ] {
+            assert!(bad.parse::<Region>().is_err(), "{:?} parsed", bad);
+        }
+    }
+
+    #[test]
+    fn test_conventions() {
+        let r = Region::parse_as::<ZeroHalfOpen>("chr1:100-200").unwrap();
+        assert_eq!(r, Region::new("chr1", Interval::new(100, 199)));
+        assert_eq!(r.to_string(), "chr1:101-200");
+        assert_eq!(r.to_string_as::<ZeroHalfOpen>().unwrap(), "chr1:100-200");
+        assert_eq!(r.to_string_as::<Interval>().unwrap(), "chr1:100-199");
+
+        let p = Region::parse_as::<ZeroHalfOpen>("chr1:100").unwrap();
+        assert_eq!(p.interval, Some(Interval::new(100, 100)));
+        assert_eq!(p.to_string_as::<ZeroHalfOpen>().unwrap(), "chr1:100");
+        assert!(Region::parse_as::<ZeroHalfOpen>("chr1:100-100").is_err());
+        assert_eq!(
+            Region::parse_as::<Interval>("chr1:0-0").unwrap().interval,
+            Some(Interval::new(0, 0))
+        );
+        assert!(Region::new("chr1", Interval::new(MAX, MAX))
+            .to_string_as::<OneClosed>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for r in [
+            Region::whole("chr7"),
+            Region::whole("HLA-A*01:01"),
+            Region::new("chr1", Interval::new(0, 0)),
+            Region::new("chr1", Interval::new(9, 19)),
+            Region::new("chr1", Interval::new(9, MAX)),
+            Region::new("a:b", Interval::new(0, MAX - 1)),
+        ] {
+            assert_eq!(r.to_string().parse::<Region>().unwrap(), r);
+            let s = r.to_string_as::<ZeroHalfOpen>().unwrap();
+            assert_eq!(Region::parse_as::<ZeroHalfOpen>(&s).unwrap(), r);
+        }
+        assert_eq!(Region::whole("chr7").to_interval(), Interval::new(0, MAX));
+    }
+}
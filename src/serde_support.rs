@@ -0,0 +1,109 @@
+//! Support for serializing a [`Stabby`] with `serde`, enabled with the
+//! `serde` feature.
+//!
+//! The serialized form holds the domain of endpoints and the intervals
+//! mapped into the dense domain, rather than the full data structure. It
+//! is therefore compact, and loading it involves no sorting or ranking,
+//! just the linear sweep that builds the dense structure. The domain and
+//! intervals are validated before anything is built.
+
+use alloc::vec::Vec;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    dense::DenseInterval,
+    domain::{Domain, FromPoints},
+    Stabby,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Stabby")]
+struct StabbyRepr {
+    domain: Vec<u64>,
+    intervals: Vec<DenseInterval>,
+}
+
+impl StabbyRepr {
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.domain.first() != Some(&0) {
+            return Err("domain must begin with 0");
+        }
+        if self.domain.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("domain is not strictly increasing");
+        }
+        let d = 2 * self.domain.len();
+        for y in self.intervals.iter() {
+            if y.first > y.last || y.last >= d || y.first % 2 != 0 || y.last % 2 != 0 {
+                return Err("interval lies outside the domain");
+            }
+        }
+        if self.intervals.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("intervals are not sorted and distinct");
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Stabby {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (domain, intervals) = self.parts();
+        let repr = StabbyRepr { domain, intervals };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Stabby {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Stabby, D::Error> {
+        let repr = StabbyRepr::deserialize(deserializer)?;
+        repr.validate().map_err(D::Error::custom)?;
+        Ok(Stabby::from_dense(
+            Domain::from_points(repr.domain),
+            &repr.intervals,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Interval;
+
+    use super::*;
+    use crate::test_util::{intervals, many_intervals};
+
+    #[test]
+    fn test_interval_round_trip() {
+        let x = Interval::new(45_331_182, 45_331_334);
+        let text = serde_json::to_string(&x).unwrap();
+        assert_eq!(text, r#"{"first":45331182,"last":45331334}"#);
+        assert_eq!(serde_json::from_str::<Interval>(&text).unwrap(), x);
+    }
+
+    #[test]
+    fn test_stabby_round_trip() {
+        for xs in [intervals(), many_intervals()] {
+            let s = Stabby::new(&xs);
+            let text = serde_json::to_string(&s).unwrap();
+            let t: Stabby = serde_json::from_str(&text).unwrap();
+            assert_eq!(t.parts(), s.parts());
+            for q in 0..600 {
+                assert_eq!(t.stab(q), s.stab(q));
+                let qi = Interval::new(q, q + 11);
+                assert_eq!(t.stab_interval(&qi), s.stab_interval(&qi));
+            }
+        }
+    }
+
+    #[test]
+    fn test_stabby_validation() {
+        for text in [
+            r#"{"domain":[1,10],"intervals":[]}"#,
+            r#"{"domain":[0,10,10],"intervals":[]}"#,
+            r#"{"domain":[0,10],"intervals":[{"first":0,"last":4}]}"#,
+            r#"{"domain":[0,10,20],"intervals":[{"first":2,"last":1}]}"#,
+            r#"{"domain":[0,10,20],"intervals":[{"first":2,"last":4},{"first":2,"last":2}]}"#,
+        ] {
+            assert!(serde_json::from_str::<Stabby>(text).is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::{Interval, Stabby};
+
+/// A handle to an index that can be replaced while it is in use, for
+/// long-running services that reload their indexes.
+///
+/// Clones of the handle share the same slot. Readers query it without
+/// taking a lock, and a writer may at any time [`store`](SharedStabby::store)
+/// a new index in the slot; queries already underway finish against the
+/// index they started with. A reader wanting several queries answered by
+/// the same version of the index should take a [`snapshot`](SharedStabby::snapshot),
+/// which keeps that version alive for as long as it is held.
+///
+#[derive(Clone)]
+pub struct SharedStabby {
+    slot: Arc<ArcSwap<Stabby>>,
+}
+
+impl SharedStabby {
+    /// Create a handle holding the given index.
+    ///
+    pub fn new(index: Stabby) -> SharedStabby {
+        SharedStabby {
+            slot: Arc::new(ArcSwap::from_pointee(index)),
+        }
+    }
+
+    /// The current version of the index.
+    ///
+    pub fn snapshot(&self) -> Arc<Stabby> {
+        self.slot.load_full()
+    }
+
+    /// Replace the index, for all clones of the handle.
+    ///
+    pub fn store(&self, index: Stabby) {
+        self.slot.store(Arc::new(index));
+    }
+
+    /// Replace the index, for all clones of the handle, returning the
+    /// version replaced.
+    ///
+    pub fn replace(&self, index: Stabby) -> Arc<Stabby> {
+        self.slot.swap(Arc::new(index))
+    }
+
+    /// Test if a position stabs any intervals in the current index.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        self.slot.load().stabs(q)
+    }
+
+    /// Retrieve the list of intervals in the current index that
+    /// are stabbed by the given position. The intervals are
+    /// returned in sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        self.slot.load().stab(q)
+    }
+
+    /// Find the list of intervals in the current index that intersect
+    /// the given query interval. The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        self.slot.load().stab_interval(q)
+    }
+}
+
+impl From<Stabby> for SharedStabby {
+    fn from(value: Stabby) -> Self {
+        SharedStabby::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+    };
+
+    use super::*;
+
+    fn version(v: u64) -> Stabby {
+        let xs: Vec<Interval> = (0..100)
+            .map(|i| Interval::new(i * 10, i * 10 + v))
+            .collect();
+        Stabby::new(&xs)
+    }
+
+    #[test]
+    fn test_replace() {
+        let shared = SharedStabby::new(version(1));
+        let other = shared.clone();
+        let old = shared.snapshot();
+        assert!(!other.stabs(5));
+        let replaced = shared.replace(version(5));
+        assert!(Arc::ptr_eq(&old, &replaced));
+        assert!(other.stabs(5));
+        assert!(!old.stabs(5));
+        assert_eq!(other.stab(995), vec![Interval::new(990, 995)]);
+    }
+
+    #[test]
+    fn test_readers_during_swaps() {
+        let shared = SharedStabby::from(version(1));
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                let done = done.clone();
+                thread::spawn(move || {
+                    while !done.load(Ordering::Relaxed) {
+                        // Every version holds one interval per multiple of
+                        // 10, so each query sees exactly one, whichever
+                        // version it runs against.
+                        let s = shared.snapshot();
+                        for q in (0..1000).step_by(10) {
+                            assert_eq!(s.stab(q).len(), 1);
+                        }
+                        assert_eq!(shared.stab(500).len(), 1);
+                    }
+                })
+            })
+            .collect();
+        for v in 2..50 {
+            shared.store(version(v % 9));
+        }
+        done.store(true, Ordering::Relaxed);
+        for h in readers {
+            h.join().unwrap();
+        }
+        assert_eq!(shared.stab(3), vec![Interval::new(0, 49 % 9)]);
+    }
+}
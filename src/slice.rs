@@ -0,0 +1,135 @@
+use alloc::vec::Vec;
+
+use crate::{
+    dense::DenseInterval,
+    domain::{to_dense, Domain, DomainMap, FromPoints},
+    sparse::Repr,
+    Interval, Stabby,
+};
+
+impl Stabby {
+    /// Build an index over just the intervals that overlap a region,
+    /// for example to hand a worker the part of a genome-wide index
+    /// relevant to it.
+    ///
+    /// The intervals are found with [`Stabby::stab_interval`], already in
+    /// sorted order, and their endpoints are taken from this index's
+    /// domain by rank, so only those ranks need to be sorted, rather than
+    /// the intervals themselves.
+    ///
+    pub fn slice(&self, region: &Interval) -> Stabby {
+        let (domain, dense) = match &self.repr {
+            Repr::Small(_) => return Stabby::small(self.stab_interval(region)),
+            Repr::Full { domain, dense, .. } => (domain, dense),
+        };
+        let qd = DenseInterval::new(
+            to_dense(domain, region.first),
+            to_dense(domain, region.last),
+        );
+        let ys = dense.stab_interval(&qd);
+
+        let mut ranks: Vec<usize> = ys.iter().flat_map(|y| [y.first / 2, y.last / 2]).collect();
+        ranks.push(0);
+        ranks.sort_unstable();
+        ranks.dedup();
+        let points: Vec<u64> = ranks.iter().map(|r| domain.select(*r)).collect();
+        let rerank = |y: usize| ranks.binary_search(&(y / 2)).unwrap() * 2;
+        let ys: Vec<DenseInterval> = ys
+            .iter()
+            .map(|y| DenseInterval::new(rerank(y.first), rerank(y.last)))
+            .collect();
+
+        Stabby::from_dense(Domain::from_points(points), &ys)
+    }
+
+    /// Build an index over the intervals that overlap a region, as with
+    /// [`Stabby::slice`], but with each interval clipped to the region.
+    ///
+    /// Intervals that become identical when clipped appear just once.
+    ///
+    pub fn slice_clipped(&self, region: &Interval) -> Stabby {
+        let mut xs: Vec<Interval> = self
+            .stab_interval(region)
+            .into_iter()
+            .map(|x| Interval::new(x.first.max(region.first), x.last.min(region.last)))
+            .collect();
+        xs.sort_unstable();
+        xs.dedup();
+        Stabby::new(&xs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, many_intervals};
+
+    #[test]
+    fn test_slice() {
+        for xs in [intervals(), many_intervals()] {
+            check_slices(&xs);
+        }
+    }
+
+    fn check_slices(xs: &[Interval]) {
+        let s = Stabby::new(xs);
+        for (first, last) in [
+            (0, 5),
+            (0, 400),
+            (35, 45),
+            (55, 55),
+            (160, 205),
+            (201, 209),
+            (305, 400),
+        ] {
+            let region = Interval::new(first, last);
+            let expected: Vec<Interval> = xs
+                .iter()
+                .filter(|x| x.first <= region.last && region.first <= x.last)
+                .copied()
+                .collect();
+            let t = s.slice(&region);
+            assert_eq!(t.parts(), Stabby::new(&expected).parts());
+            for q in 0..600 {
+                let want: Vec<Interval> = expected
+                    .iter()
+                    .filter(|x| x.first <= q && q <= x.last)
+                    .copied()
+                    .collect();
+                assert_eq!(t.stab(q), want);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_clipped() {
+        let s = Stabby::new(&intervals());
+        let t = s.slice_clipped(&Interval::new(45, 205));
+        assert_eq!(
+            t.stab(45),
+            vec![
+                Interval::new(45, 50),
+                Interval::new(45, 60),
+                Interval::new(45, 100),
+                Interval::new(45, 150),
+                Interval::new(45, 200),
+                Interval::new(45, 205),
+            ]
+        );
+        assert!(t.stab(44).is_empty());
+        assert_eq!(t.stab(203), vec![Interval::new(45, 205)]);
+
+        let xs = many_intervals();
+        let region = Interval::new(45, 205);
+        let t = Stabby::new(&xs).slice_clipped(&region);
+        let mut want: Vec<Interval> = xs
+            .iter()
+            .filter(|x| x.first <= region.last && region.first <= x.last)
+            .map(|x| Interval::new(x.first.max(45), x.last.min(205)))
+            .collect();
+        want.sort();
+        want.dedup();
+        assert!(want.len() > 32);
+        assert_eq!(t.iter().collect::<Vec<Interval>>(), want);
+    }
+}
@@ -1,11 +1,21 @@
-use std::fmt::Display;
+use alloc::{borrow::Cow, vec::Vec};
+use core::{
+    fmt::Display,
+    ops::{Bound, Range, RangeBounds, RangeInclusive},
+};
 
-use ransel::{rank::Rank, select::Select, sorted::Sorted};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
-use crate::dense::{DenseInterval, DenseStabby};
+use crate::{
+    dense::{DenseInterval, DenseLookup, DenseStabby},
+    domain::{to_dense, to_sparse, Domain, DomainMap, FromPoints, MaybeSync},
+    StabbyError,
+};
 
 /// The Interval struct represents a closed interval on an (unsigned) integer domain.
 #[derive(Clone, Copy, Eq, PartialOrd, Ord, Default, Hash, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interval {
     /// Lower bound of the interval
     pub first: u64,
@@ -28,60 +38,196 @@ impl From<(u64, u64)> for Interval {
     }
 }
 
+/// Convert a half-open range to the closed interval it covers.
+///
+/// # Panics
+///
+/// Panics if the range is empty, since an interval holds at least one
+/// position.
+///
+impl From<Range<u64>> for Interval {
+    fn from(value: Range<u64>) -> Self {
+        assert!(value.start < value.end, "empty range {:?}", value);
+        Interval::new(value.start, value.end - 1)
+    }
+}
+
+/// Convert a closed range to an interval.
+///
+/// # Panics
+///
+/// Panics if the range is empty, since an interval holds at least one
+/// position.
+///
+impl From<RangeInclusive<u64>> for Interval {
+    fn from(value: RangeInclusive<u64>) -> Self {
+        assert!(!value.is_empty(), "empty range {:?}", value);
+        Interval::new(*value.start(), *value.end())
+    }
+}
+
 impl Display for Interval {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "[{}, {}]", self.first, self.last)
     }
 }
 
+/// The closed interval covered by a range of positions, or `None` if the
+/// range is empty.
+///
+pub(crate) fn closed_range<R: RangeBounds<u64>>(range: &R) -> Option<Interval> {
+    let first = match range.start_bound() {
+        Bound::Included(x) => *x,
+        Bound::Excluded(x) => x.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let last = match range.end_bound() {
+        Bound::Included(x) => *x,
+        Bound::Excluded(x) => x.checked_sub(1)?,
+        Bound::Unbounded => u64::MAX,
+    };
+    (first <= last).then(|| Interval::new(first, last))
+}
+
 /// The Jens Schmidt data structure for representing a set of (closed)
 /// intervals over an unsigned integer domain.
 ///
-pub struct Stabby {
-    domain: Sorted,
-    dense: DenseStabby,
+/// The endpoints of the intervals are mapped into a dense domain by a
+/// [`DomainMap`], which by default is a sorted vector of the endpoints,
+/// searched by binary search.
+/// Another may be chosen by building the index with
+/// [`with_domain`](Stabby::with_domain).
+///
+/// An index over no more than 32 intervals skips all of this,
+/// and keeps just the sorted intervals, which it scans. For so few, a scan
+/// is as quick as the full structure, and takes a fraction of the space.
+/// The results of queries are the same either way.
+///
+/// Once built, a `Stabby` is never modified by queries, and it is both
+/// `Send` and `Sync`, so it may be shared between threads behind an
+/// [`Arc`](std::sync::Arc) and queried from all of them at once.
+///
+pub struct Stabby<D = Domain> {
+    pub(crate) repr: Repr<D>,
 }
 
+/// The largest number of intervals for which a [`Stabby`] keeps just the
+/// sorted intervals, rather than building the full data structure.
 ///
+pub(crate) const SMALL_MAX: usize = 32;
+
+/// The representation of a [`Stabby`]. There is one per index, so the
+/// full structure is held inline rather than boxed, to save an indirection
+/// on every query.
+///
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Repr<D> {
+    /// The intervals alone, in sorted order.
+    Small(Vec<Interval>),
+    /// The domain and the data structure over the dense domain, and the
+    /// number of intervals.
+    Full {
+        domain: D,
+        dense: DenseStabby,
+        len: usize,
+    },
+}
+
 impl Stabby {
     /// Create a new Stabby data structure. The list of intervals
     /// must be in natural sorted order, and free of duplicates.
     ///
     pub fn new(xs: &[Interval]) -> Stabby {
-        let domain = Self::make_domain(xs);
-        let mut ys: Vec<DenseInterval> = Vec::new();
-        let mut y_max = 0;
-        for x in xs.iter() {
-            let (y_f, y_l) = domain.rank_2(x.first as u64, x.last as u64);
-            let y = DenseInterval::new(y_f * 2, y_l * 2);
-            if y.last > y_max {
-                y_max = y.last;
-            }
-            ys.push(y);
+        Stabby::with_domain(xs)
+    }
+}
+
+impl<D: FromPoints + MaybeSync> Stabby<D> {
+    /// Create a new Stabby data structure, with the given representation
+    /// of its domain. The list of intervals must be in natural sorted
+    /// order, and free of duplicates.
+    ///
+    /// ```rust
+    /// use stabby::{EytzingerDomain, Interval, Stabby};
+    ///
+    /// let idx: Stabby<EytzingerDomain> =
+    ///     Stabby::with_domain(&[Interval::new(10, 20), Interval::new(15, 30)]);
+    /// assert_eq!(idx.stab(17).len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain cannot hold the endpoints of the intervals,
+    /// where [`try_with_domain`](Stabby::try_with_domain) fails.
+    ///
+    pub fn with_domain(xs: &[Interval]) -> Stabby<D> {
+        match Self::try_with_domain(xs) {
+            Ok(s) => s,
+            Err(e) => panic!("{}", e),
         }
-        ys.sort();
-        let dense = DenseStabby::new(y_max as usize + 1, &ys);
+    }
 
-        Stabby { domain, dense }
+    /// Create a new Stabby data structure, with the given representation
+    /// of its domain, failing if the domain cannot hold the endpoints of
+    /// the intervals.
+    ///
+    pub fn try_with_domain(xs: &[Interval]) -> Result<Stabby<D>, StabbyError> {
+        if xs.len() <= SMALL_MAX {
+            return Ok(Stabby::small(xs.to_vec()));
+        }
+        let domain = D::try_from_points(domain_points(xs))?;
+        let mut ys = rank_all(&domain, xs);
+        sort_all(&mut ys);
+        Ok(Self::from_dense(domain, &ys))
     }
+}
 
-    fn make_domain(xs: &[Interval]) -> Sorted {
-        let mut ys: Vec<u64> = Vec::new();
-        ys.push(0);
-        for x in xs.iter() {
-            ys.push(x.first);
-            ys.push(x.last);
+impl<D> Stabby<D> {
+    /// Hold a few intervals, in sorted order, without building the full
+    /// data structure.
+    ///
+    pub(crate) fn small(xs: Vec<Interval>) -> Stabby<D> {
+        Stabby {
+            repr: Repr::Small(xs),
         }
-        ys.sort();
-        ys.dedup();
-        Sorted::new(&ys)
+    }
+
+    /// Assemble an index from its two layers, whatever its size.
+    ///
+    pub(crate) fn from_layers(domain: D, dense: DenseStabby) -> Stabby<D> {
+        // Each interval is either the longest starting at its start, or
+        // one of the shorter ones listed with it.
+        let len = (0..dense.size())
+            .filter_map(|p| dense.start(p).filter(|a| a.first == p))
+            .map(|a| 1 + dense.smaller(&a).count())
+            .sum();
+        Stabby {
+            repr: Repr::Full { domain, dense, len },
+        }
+    }
+}
+
+impl<D: DomainMap> Stabby<D> {
+    /// Build the data structure from a domain and the intervals already
+    /// mapped into the dense domain, in sorted order.
+    ///
+    pub(crate) fn from_dense(domain: D, ys: &[DenseInterval]) -> Stabby<D> {
+        if ys.len() <= SMALL_MAX {
+            return Stabby::small(to_sparse_intervals(&domain, ys));
+        }
+        let y_max = ys.iter().map(|y| y.last).max().unwrap_or(0);
+        let dense = DenseStabby::new(y_max + 1, ys);
+
+        Stabby::from_layers(domain, dense)
     }
 
     /// Test if a position stabs any intervals.
     ///
     pub fn stabs(&self, q: u64) -> bool {
-        let qd = self.sparse_to_dense(q);
-        self.dense.stabs(qd)
+        match &self.repr {
+            Repr::Small(xs) => xs.iter().take_while(|x| x.first <= q).any(|x| q <= x.last),
+            Repr::Full { domain, dense, .. } => stabs_with(domain, dense, q),
+        }
     }
 
     /// Retrieve the list of intervals that are stabbed by
@@ -89,48 +235,352 @@ impl Stabby {
     /// sorted order.
     ///
     pub fn stab(&self, q: u64) -> Vec<Interval> {
-        let qd = self.sparse_to_dense(q);
-        let ys = self.dense.stab(qd);
-        let mut xs: Vec<Interval> = Vec::new();
-        for y in ys.iter() {
-            let y1 = self.dense_to_sparse(y.first);
-            let y2 = self.dense_to_sparse(y.last);
-            xs.push(Interval::new(y1, y2));
+        match &self.repr {
+            Repr::Small(xs) => xs
+                .iter()
+                .take_while(|x| x.first <= q)
+                .filter(|x| q <= x.last)
+                .copied()
+                .collect(),
+            Repr::Full { domain, dense, .. } => stab_with(domain, dense, q),
         }
-        xs
     }
 
     /// Find the list of intervals that intersect the given query interval.
     /// The results are returned in sorted order.
     ///
     pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
-        let qd = DenseInterval::new(self.sparse_to_dense(q.first), self.sparse_to_dense(q.last));
-        println!("qd = {:?}", qd);
-        println!("dense = {:?}", self.dense);
-        let ys = self.dense.stab_interval(&qd);
-        let mut xs: Vec<Interval> = Vec::new();
-        for y in ys.iter() {
-            let y1 = self.dense_to_sparse(y.first as usize);
-            let y2 = self.dense_to_sparse(y.last as usize);
-            xs.push(Interval::new(y1, y2));
+        match &self.repr {
+            Repr::Small(xs) => xs
+                .iter()
+                .take_while(|x| x.first <= q.last)
+                .filter(|x| q.first <= x.last)
+                .copied()
+                .collect(),
+            Repr::Full { domain, dense, .. } => stab_interval_with(domain, dense, q),
         }
-        xs
     }
 
-    fn sparse_to_dense(&self, x: u64) -> usize {
-        let (r1, r2) = self.domain.rank_2(x as u64, (x + 1) as u64);
-        let q = if r1 != r2 { r1 * 2 } else { r1 * 2 - 1 };
-        q
+    /// Find the list of intervals that intersect a range of positions, such
+    /// as `a..b`, `a..=b`, `..b`, `a..` or `..`. The results are returned
+    /// in sorted order, and an empty range intersects nothing.
+    ///
+    /// ```rust
+    /// use stabby::{Interval, Stabby};
+    ///
+    /// let idx = Stabby::new(&[Interval::new(10, 20), Interval::new(30, 40)]);
+    /// assert_eq!(idx.stab_range(20..30), vec![Interval::new(10, 20)]);
+    /// assert_eq!(idx.stab_range(21..=30), vec![Interval::new(30, 40)]);
+    /// assert!(idx.stab_range(25..25).is_empty());
+    /// ```
+    ///
+    pub fn stab_range<R: RangeBounds<u64>>(&self, range: R) -> Vec<Interval> {
+        match closed_range(&range) {
+            None => Vec::new(),
+            Some(q) => self.stab_interval(&q),
+        }
     }
 
-    fn dense_to_sparse(&self, x: usize) -> u64 {
-        self.domain.select(x / 2)
+    /// Recover the intervals in the index, in sorted order.
+    ///
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn intervals(&self) -> Vec<Interval> {
+        match &self.repr {
+            Repr::Small(xs) => xs.clone(),
+            Repr::Full { domain, dense, .. } => to_sparse_intervals(domain, &dense.intervals()),
+        }
     }
+
+    /// The points of the domain, and the intervals mapped into the dense
+    /// domain, in sorted order. Together they determine the index exactly,
+    /// and for a small index, they are what the full structure would be
+    /// built from.
+    ///
+    pub(crate) fn parts(&self) -> (Vec<u64>, Vec<DenseInterval>) {
+        match &self.repr {
+            Repr::Small(xs) => small_parts(xs),
+            Repr::Full { domain, dense, .. } => (domain_select_all(domain), dense.intervals()),
+        }
+    }
+
+    /// The points of the domain and the data structure over the dense
+    /// domain, building the latter if the index is small.
+    ///
+    pub(crate) fn layers(&self) -> (Vec<u64>, Cow<'_, DenseStabby>) {
+        match &self.repr {
+            Repr::Small(xs) => {
+                let (points, dense) = small_layers(xs);
+                (points, Cow::Owned(dense))
+            }
+            Repr::Full { domain, dense, .. } => (domain_select_all(domain), Cow::Borrowed(dense)),
+        }
+    }
+
+    /// Take the index apart into its two layers, as with [`Stabby::layers`].
+    ///
+    pub(crate) fn into_layers(self) -> (Vec<u64>, DenseStabby) {
+        match self.repr {
+            Repr::Small(xs) => small_layers(&xs),
+            Repr::Full { domain, dense, .. } => (domain_select_all(&domain), dense),
+        }
+    }
+}
+
+/// All the points of a domain, in order.
+///
+fn domain_select_all<D: DomainMap>(domain: &D) -> Vec<u64> {
+    (0..domain.count()).map(|i| domain.select(i)).collect()
+}
+
+/// The parts of the full data structure over a few intervals.
+///
+fn small_parts(xs: &[Interval]) -> (Vec<u64>, Vec<DenseInterval>) {
+    let points = domain_points(xs);
+    let ys = rank_all(&points, xs);
+    (points, ys)
+}
+
+/// Build the full data structure over a few intervals.
+///
+fn small_layers(xs: &[Interval]) -> (Vec<u64>, DenseStabby) {
+    let (points, ys) = small_parts(xs);
+    let y_max = ys.iter().map(|y| y.last).max().unwrap_or(0);
+    (points, DenseStabby::new(y_max + 1, &ys))
+}
+
+/// Sort the endpoints or intervals used in construction, in parallel when
+/// the `rayon` feature is enabled. The elements are totally ordered, so
+/// the result is the same either way.
+///
+fn sort_all<T: Ord + Send>(xs: &mut [T]) {
+    #[cfg(feature = "rayon")]
+    xs.par_sort_unstable();
+    #[cfg(not(feature = "rayon"))]
+    xs.sort_unstable();
+}
+
+/// The sorted, distinct endpoints of a list of intervals, together with 0.
+///
+pub(crate) fn domain_points(xs: &[Interval]) -> Vec<u64> {
+    let mut ys: Vec<u64> = Vec::new();
+    ys.push(0);
+    for x in xs.iter() {
+        ys.push(x.first);
+        ys.push(x.last);
+    }
+    sort_all(&mut ys);
+    ys.dedup();
+    ys
+}
+
+fn rank_one<D: DomainMap>(domain: &D, x: &Interval) -> DenseInterval {
+    DenseInterval::new(domain.rank(x.first) * 2, domain.rank(x.last) * 2)
+}
+
+/// Map each interval into the dense domain.
+///
+#[cfg(not(feature = "rayon"))]
+pub(crate) fn rank_all<D: DomainMap>(domain: &D, xs: &[Interval]) -> Vec<DenseInterval> {
+    xs.iter().map(|x| rank_one(domain, x)).collect()
+}
+
+/// Map each interval into the dense domain, in parallel.
+///
+#[cfg(feature = "rayon")]
+pub(crate) fn rank_all<D: DomainMap + Sync>(domain: &D, xs: &[Interval]) -> Vec<DenseInterval> {
+    xs.par_iter().map(|x| rank_one(domain, x)).collect()
+}
+
+/// Map a list of intervals over the dense domain back to the sparse domain.
+///
+pub(crate) fn to_sparse_intervals<D: DomainMap>(domain: &D, ys: &[DenseInterval]) -> Vec<Interval> {
+    let mut xs: Vec<Interval> = Vec::new();
+    for y in ys.iter() {
+        let y1 = to_sparse(domain, y.first);
+        let y2 = to_sparse(domain, y.last);
+        xs.push(Interval::new(y1, y2));
+    }
+    xs
+}
+
+/// The implementation of [`Stabby::stabs`] over any representation of the
+/// two layers of the data structure.
+///
+pub(crate) fn stabs_with<D: DomainMap, L: DenseLookup>(domain: &D, dense: &L, q: u64) -> bool {
+    dense.stabs(to_dense(domain, q))
+}
+
+/// The implementation of [`Stabby::stab`] over any representation of the
+/// two layers of the data structure.
+///
+pub(crate) fn stab_with<D: DomainMap, L: DenseLookup>(
+    domain: &D,
+    dense: &L,
+    q: u64,
+) -> Vec<Interval> {
+    to_sparse_intervals(domain, &dense.stab(to_dense(domain, q)))
+}
+
+/// The implementation of [`Stabby::stab_interval`] over any representation
+/// of the two layers of the data structure.
+///
+pub(crate) fn stab_interval_with<D: DomainMap, L: DenseLookup>(
+    domain: &D,
+    dense: &L,
+    q: &Interval,
+) -> Vec<Interval> {
+    let qd = DenseInterval::new(to_dense(domain, q.first), to_dense(domain, q.last));
+    to_sparse_intervals(domain, &dense.stab_interval(&qd))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{sync::Arc, thread};
+
     use super::*;
+    use crate::test_util::MiniRng;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_stabby_random() {
+        for seed in 1..=20 {
+            let mut rng = MiniRng::new(seed);
+            let mut src: Vec<Interval> = Vec::new();
+            for _i in 0..200 {
+                let first = rng.rnd() % 500;
+                src.push(Interval::new(first, first + rng.rnd() % 50));
+            }
+            src.sort();
+            src.dedup();
+            let s = Stabby::new(&src);
+            for q in 0..560 {
+                let expected: Vec<Interval> = src
+                    .iter()
+                    .filter(|x| x.first <= q && q <= x.last)
+                    .copied()
+                    .collect();
+                assert_eq!(s.stabs(q), !expected.is_empty());
+                assert_eq!(s.stab(q), expected);
+                for w in [0, 1, 13, 100] {
+                    let qi = Interval::new(q, q + w);
+                    let expected: Vec<Interval> = src
+                        .iter()
+                        .filter(|x| x.first <= qi.last && qi.first <= x.last)
+                        .copied()
+                        .collect();
+                    assert_eq!(s.stab_interval(&qi), expected);
+                }
+            }
+        }
+    }
+
+    /// Build the full data structure, however few the intervals.
+    fn full(xs: &[Interval]) -> Stabby {
+        let (points, dense) = small_layers(xs);
+        Stabby::from_layers(Domain::from_points(points), dense)
+    }
+
+    #[test]
+    fn test_small() {
+        let mut rng = MiniRng::new(7);
+        for n in [0, 1, 2, 5, SMALL_MAX, SMALL_MAX + 1, 40] {
+            let mut src: Vec<Interval> = (0..n)
+                .map(|_| {
+                    let first = rng.rnd() % 200;
+                    Interval::new(first, first + rng.rnd() % 40)
+                })
+                .collect();
+            src.sort();
+            src.dedup();
+            let s = Stabby::new(&src);
+            assert_eq!(matches!(s.repr, Repr::Small(_)), src.len() <= SMALL_MAX);
+            let t = full(&src);
+            assert_eq!(s.parts(), t.parts());
+            for q in 0..260 {
+                assert_eq!(s.stabs(q), t.stabs(q));
+                assert_eq!(s.stab(q), t.stab(q));
+                let qi = Interval::new(q, q + 9);
+                assert_eq!(s.stab_interval(&qi), t.stab_interval(&qi));
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_stab_range() {
+        let xs: Vec<Interval> = (0..50u64)
+            .map(|i| Interval::new(i * 10, i * 10 + 5))
+            .collect();
+        for s in [Stabby::new(&xs), Stabby::new(&xs[..3])] {
+            assert_eq!(s.stab_range(6..10), vec![]);
+            assert_eq!(s.stab_range(6..11), s.stab_interval(&Interval::new(10, 10)));
+            assert_eq!(s.stab_range(5..=10), s.stab_interval(&Interval::new(5, 10)));
+            assert_eq!(s.stab_range(..11), s.stab_interval(&Interval::new(0, 10)));
+            assert_eq!(s.stab_range(..=0), s.stab(0));
+            assert_eq!(
+                s.stab_range(25..),
+                s.stab_interval(&Interval::new(25, u64::MAX))
+            );
+            assert_eq!(s.stab_range(..), s.iter().collect::<Vec<Interval>>());
+            assert!(s.stab_range(..0).is_empty());
+            assert!(s.stab_range(7..7).is_empty());
+            assert!(s.stab_range(9..=8).is_empty());
+            assert!(s
+                .stab_range((Bound::Excluded(u64::MAX), Bound::Unbounded))
+                .is_empty());
+            assert_eq!(
+                s.stab_range((Bound::Excluded(4), Bound::Excluded(11))),
+                s.stab_interval(&Interval::new(5, 10))
+            );
+        }
+    }
+
+    #[test]
+    fn test_interval_from_range() {
+        assert_eq!(Interval::from(3..7), Interval::new(3, 6));
+        assert_eq!(Interval::from(3..=7), Interval::new(3, 7));
+        assert_eq!(Interval::from(0..=u64::MAX), Interval::new(0, u64::MAX));
+        assert_eq!(
+            Interval::from(u64::MAX - 1..u64::MAX),
+            Interval::new(u64::MAX - 1, u64::MAX - 1)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interval_from_empty_range() {
+        let _ = Interval::from(5..5);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<Stabby>();
+        #[cfg(feature = "std")]
+        assert_send_sync::<crate::MappedStabby>();
+
+        let src: Vec<Interval> = (0..1000)
+            .map(|i| Interval::new(i * 10, i * 10 + 25))
+            .collect();
+        let idx = Arc::new(Stabby::new(&src));
+        let handles: Vec<thread::JoinHandle<()>> = (0..4)
+            .map(|t| {
+                let idx = idx.clone();
+                thread::spawn(move || {
+                    for q in (t..10_000).step_by(4) {
+                        let expected: Vec<Interval> = (0..1000)
+                            .map(|i| Interval::new(i * 10, i * 10 + 25))
+                            .filter(|x| x.first <= q && q <= x.last)
+                            .collect();
+                        assert_eq!(idx.stab(q), expected);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
 
     #[test]
     fn test_stabby_1() {
@@ -143,20 +593,21 @@ mod tests {
             Interval::new(45334391, 45334511),
             Interval::new(45340219, 45340447),
         ];
-        let idx = Stabby::new(&mutyh);
-        assert!(idx.stabs(45_331_258));
-        assert!(!idx.stabs(45_332_840));
-        assert_eq!(
-            idx.stab(45_331_258),
-            vec![Interval::new(45_331_182, 45_331_334)]
-        );
-        assert_eq!(
-            idx.stab_interval(&Interval::new(45_331_151, 45_331_880)),
-            vec![
-                Interval::new(45_331_182, 45_331_334),
-                Interval::new(45_331_420, 45_331_556)
-            ]
-        );
+        for idx in [Stabby::new(&mutyh), full(&mutyh)] {
+            assert!(idx.stabs(45_331_258));
+            assert!(!idx.stabs(45_332_840));
+            assert_eq!(
+                idx.stab(45_331_258),
+                vec![Interval::new(45_331_182, 45_331_334)]
+            );
+            assert_eq!(
+                idx.stab_interval(&Interval::new(45_331_151, 45_331_880)),
+                vec![
+                    Interval::new(45_331_182, 45_331_334),
+                    Interval::new(45_331_420, 45_331_556)
+                ]
+            );
+        }
     }
 
     #[test]
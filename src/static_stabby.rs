@@ -0,0 +1,364 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Debug, Write};
+
+use crate::{
+    dense::{DenseInterval, DenseLookup},
+    domain::DomainMap,
+    sparse::{stab_interval_with, stab_with, stabs_with},
+    Interval, Stabby, StabbyError,
+};
+
+/// A dense interval, as a pair of positions.
+type Pair = (usize, usize);
+
+fn pair(y: &DenseInterval) -> Pair {
+    (y.first, y.last)
+}
+
+fn unpair(p: &Pair) -> DenseInterval {
+    DenseInterval::new(p.0, p.1)
+}
+
+/// An index held entirely in `static` data, for sets of intervals that are
+/// fixed when a program is built, such as tables of Unicode ranges or a
+/// panel of genes.
+///
+/// A `StaticStabby` is not built at run time. Instead, a build script
+/// builds a [`Stabby`] and writes it out as Rust source with
+/// [`Stabby::static_source`], which the program then includes:
+///
+/// ```rust,ignore
+/// // build.rs
+/// let idx = stabby::Stabby::new(&panel);
+/// let path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("panel.rs");
+/// std::fs::write(path, idx.static_source("PANEL").unwrap()).unwrap();
+///
+/// // src/lib.rs
+/// include!(concat!(env!("OUT_DIR"), "/panel.rs"));
+///
+/// fn in_panel(pos: u64) -> bool {
+///     PANEL.stabs(pos)
+/// }
+/// ```
+///
+/// The tables that [`Stabby`] keeps in hash tables are held as sorted
+/// slices, and searched by binary search.
+///
+pub struct StaticStabby {
+    domain: &'static [u64],
+    start: &'static [Option<Pair>],
+    start2: &'static [Option<Pair>],
+    parent: &'static [(Pair, Pair)],
+    left: &'static [(Pair, Pair)],
+    last: &'static [(Pair, Pair)],
+    smaller: &'static [(Pair, usize, usize)],
+    pool: &'static [Pair],
+}
+
+impl StaticStabby {
+    /// Assemble an index from its tables. This is called by the source
+    /// written by [`Stabby::static_source`], and is not meant to be called
+    /// directly: the tables are not checked, and if they are inconsistent,
+    /// queries give wrong answers or panic.
+    ///
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub const fn from_raw(
+        domain: &'static [u64],
+        start: &'static [Option<Pair>],
+        start2: &'static [Option<Pair>],
+        parent: &'static [(Pair, Pair)],
+        left: &'static [(Pair, Pair)],
+        last: &'static [(Pair, Pair)],
+        smaller: &'static [(Pair, usize, usize)],
+        pool: &'static [Pair],
+    ) -> StaticStabby {
+        StaticStabby {
+            domain,
+            start,
+            start2,
+            parent,
+            left,
+            last,
+            smaller,
+            pool,
+        }
+    }
+
+    /// Test if a position stabs any intervals.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        stabs_with(self, self, q)
+    }
+
+    /// Retrieve the list of intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        stab_with(self, self, q)
+    }
+
+    /// Find the list of intervals that intersect the given query interval.
+    /// The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        stab_interval_with(self, self, q)
+    }
+
+    fn lookup(table: &[(Pair, Pair)], a: &DenseInterval) -> Option<DenseInterval> {
+        table
+            .binary_search_by_key(&pair(a), |e| e.0)
+            .ok()
+            .map(|i| unpair(&table[i].1))
+    }
+}
+
+impl DomainMap for StaticStabby {
+    fn count(&self) -> usize {
+        self.domain.len()
+    }
+
+    fn rank(&self, x: u64) -> usize {
+        self.domain.partition_point(|p| *p < x)
+    }
+
+    fn select(&self, i: usize) -> u64 {
+        self.domain[i]
+    }
+}
+
+impl DenseLookup for StaticStabby {
+    fn size(&self) -> usize {
+        self.start.len()
+    }
+
+    fn start(&self, q: usize) -> Option<DenseInterval> {
+        self.start[q].as_ref().map(unpair)
+    }
+
+    fn start2(&self, q: usize) -> Option<DenseInterval> {
+        self.start2[q].as_ref().map(unpair)
+    }
+
+    fn parent(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        StaticStabby::lookup(self.parent, a)
+    }
+
+    fn left(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        StaticStabby::lookup(self.left, a)
+    }
+
+    fn last(&self, a: &DenseInterval) -> Option<DenseInterval> {
+        StaticStabby::lookup(self.last, a)
+    }
+
+    fn smaller(&self, a: &DenseInterval) -> impl DoubleEndedIterator<Item = DenseInterval> + '_ {
+        let run = match self.smaller.binary_search_by_key(&pair(a), |e| e.0) {
+            Ok(i) => &self.pool[self.smaller[i].1..self.smaller[i].1 + self.smaller[i].2],
+            Err(_) => &[],
+        };
+        run.iter().map(unpair)
+    }
+}
+
+/// The tables of a [`StaticStabby`], as built from a [`Stabby`].
+struct Tables {
+    domain: Vec<u64>,
+    start: Vec<Option<Pair>>,
+    start2: Vec<Option<Pair>>,
+    parent: Vec<(Pair, Pair)>,
+    left: Vec<(Pair, Pair)>,
+    last: Vec<(Pair, Pair)>,
+    smaller: Vec<(Pair, usize, usize)>,
+    pool: Vec<Pair>,
+}
+
+impl Tables {
+    fn new(s: &Stabby) -> Tables {
+        let (domain, d) = s.layers();
+        let table = |m: &crate::collections::Map<DenseInterval, DenseInterval>| {
+            let mut t: Vec<(Pair, Pair)> = m.iter().map(|(k, v)| (pair(k), pair(v))).collect();
+            t.sort_unstable();
+            t
+        };
+        let mut keys: Vec<&DenseInterval> = d.smaller.keys().collect();
+        keys.sort_unstable();
+        let mut smaller: Vec<(Pair, usize, usize)> = Vec::new();
+        let mut pool: Vec<Pair> = Vec::new();
+        for k in keys {
+            let run = &d.smaller[k];
+            smaller.push((pair(k), pool.len(), run.len()));
+            pool.extend(run.iter().map(pair));
+        }
+        Tables {
+            domain,
+            start: d.start.iter().map(|y| y.as_ref().map(pair)).collect(),
+            start2: d.start2.iter().map(|y| y.as_ref().map(pair)).collect(),
+            parent: table(&d.parent),
+            left: table(&d.left),
+            last: table(&d.last),
+            smaller,
+            pool,
+        }
+    }
+}
+
+/// Write a slice literal, a few items to a line.
+fn write_slice<T: Debug>(out: &mut String, xs: &[T]) {
+    const PER_LINE: usize = 8;
+    if xs.is_empty() {
+        out.push_str("    &[],\n");
+        return;
+    }
+    out.push_str("    &[\n");
+    for chunk in xs.chunks(PER_LINE) {
+        out.push_str("       ");
+        for x in chunk {
+            let _ = write!(out, " {:?},", x);
+        }
+        out.push('\n');
+    }
+    out.push_str("    ],\n");
+}
+
+impl Stabby {
+    /// Write the index as Rust source code defining a [`StaticStabby`]
+    /// with the given name, for a build script to generate an index that
+    /// needs no construction at run time.
+    ///
+    /// The source holds a single item, `pub static NAME: ::stabby::StaticStabby`,
+    /// and is meant to be included with `include!`. Fails if `name` is not
+    /// a plain Rust identifier.
+    ///
+    pub fn static_source(&self, name: &str) -> Result<String, StabbyError> {
+        if !is_identifier(name) {
+            return Err(StabbyError::Parse {
+                input: name.to_string(),
+                reason: "not a Rust identifier",
+            });
+        }
+        let t = Tables::new(self);
+        let mut out = String::new();
+        let _ = writeln!(out, "/// Generated by stabby; do not edit.");
+        let _ = writeln!(
+            out,
+            "pub static {}: ::stabby::StaticStabby = ::stabby::StaticStabby::from_raw(",
+            name
+        );
+        write_slice(&mut out, &t.domain);
+        write_slice(&mut out, &t.start);
+        write_slice(&mut out, &t.start2);
+        write_slice(&mut out, &t.parent);
+        write_slice(&mut out, &t.left);
+        write_slice(&mut out, &t.last);
+        write_slice(&mut out, &t.smaller);
+        write_slice(&mut out, &t.pool);
+        out.push_str(");\n");
+        Ok(out)
+    }
+}
+
+/// The words that cannot name a `static`, including those reserved
+/// for future use.
+const KEYWORDS: &[&str] = &[
+    "_", "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Test if a name is an ASCII Rust identifier that is not a keyword.
+fn is_identifier(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    match bytes.next() {
+        Some(b) if b == b'_' || b.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+    bytes.all(|b| b == b'_' || b.is_ascii_alphanumeric()) && !KEYWORDS.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, many_intervals};
+
+    /// Build a static index the way the generated source would.
+    fn leak(s: &Stabby) -> StaticStabby {
+        let t = Tables::new(s);
+        StaticStabby::from_raw(
+            t.domain.leak(),
+            t.start.leak(),
+            t.start2.leak(),
+            t.parent.leak(),
+            t.left.leak(),
+            t.last.leak(),
+            t.smaller.leak(),
+            t.pool.leak(),
+        )
+    }
+
+    #[test]
+    fn test_static_queries() {
+        for xs in [intervals(), many_intervals()] {
+            let s = Stabby::new(&xs);
+            let t = leak(&s);
+            for q in 0..600 {
+                assert_eq!(t.stabs(q), s.stabs(q));
+                assert_eq!(t.stab(q), s.stab(q));
+                let qi = Interval::new(q, q + 17);
+                assert_eq!(t.stab_interval(&qi), s.stab_interval(&qi));
+            }
+        }
+    }
+
+    #[test]
+    fn test_static_source() {
+        let s = Stabby::new(&[Interval::new(3, 5), Interval::new(3, 9)]);
+        let expected = "\
+/// Generated by stabby; do not edit.
+pub static SMALL: ::stabby::StaticStabby = ::stabby::StaticStabby::from_raw(
+    &[
+        0, 3, 5, 9,
+    ],
+    &[
+        None, None, Some((2, 6)), Some((2, 6)), Some((2, 6)), Some((2, 6)), Some((2, 6)), None,
+    ],
+    &[
+        None, None, Some((2, 6)), Some((2, 6)), Some((2, 6)), Some((2, 6)), Some((2, 6)), Some((2, 6)),
+    ],
+    &[
+        ((2, 6), (0, 0)),
+    ],
+    &[],
+    &[
+        ((0, 0), (2, 6)),
+    ],
+    &[
+        ((2, 6), 0, 1),
+    ],
+    &[
+        (2, 4),
+    ],
+);
+";
+        assert_eq!(s.static_source("SMALL").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_static_source_name() {
+        let s = Stabby::new(&intervals());
+        for good in ["PANEL", "_PANEL", "panel_2", "x"] {
+            assert!(s.static_source(good).is_ok(), "{:?}", good);
+        }
+        for bad in [
+            "", "2PANEL", "PAN EL", "PANEL;", "r#PANEL", "_", "static", "Self", "ÄPFEL",
+        ] {
+            assert!(s.static_source(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
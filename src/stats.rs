@@ -0,0 +1,292 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::{
+    collections::map_heap_bytes,
+    dense::DenseInterval,
+    domain::DomainMap,
+    sparse::{domain_points, Repr},
+    Interval, Stabby,
+};
+
+/// A summary of the lengths of the intervals in an index, counting the
+/// positions each covers.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LengthStats {
+    /// The length of the shortest interval.
+    pub min: u64,
+
+    /// The length of the longest interval.
+    pub max: u64,
+
+    /// The mean length.
+    pub mean: f64,
+
+    /// The median length, or the lower of the two middle lengths if there
+    /// is an even number of intervals.
+    pub median: u64,
+}
+
+/// Statistics describing the intervals of an index and its structure, as
+/// returned by [`Stabby::stats`].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StabbyStats {
+    /// The number of intervals.
+    pub intervals: usize,
+
+    /// The number of distinct endpoints of the intervals.
+    pub endpoints: usize,
+
+    /// Whether the index holds its intervals as a plain sorted list,
+    /// because there are so few.
+    pub small: bool,
+
+    /// The number of positions in the dense domain, or 0 for a small
+    /// index, which has none.
+    pub dense_size: usize,
+
+    /// The greatest number of intervals covering any one position.
+    pub max_depth: usize,
+
+    /// The length of the longest chain of intervals, each containing the
+    /// next.
+    pub nesting_depth: usize,
+
+    /// The lengths of the intervals, if there are any.
+    pub lengths: Option<LengthStats>,
+}
+
+/// The memory an index holds on the heap, in bytes, by component, as
+/// returned by [`Stabby::heap_size_bytes`].
+///
+/// The sizes of the tables are estimated from their capacity and the
+/// layout of the standard collections, so the figures are close, but not
+/// exact.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapSize {
+    /// The domain mapping endpoints to the dense domain.
+    pub domain: usize,
+
+    /// The `start` and `start2` arrays over the dense domain.
+    pub start: usize,
+
+    /// The `parent` table.
+    pub parent: usize,
+
+    /// The `left` table.
+    pub left: usize,
+
+    /// The `last` table.
+    pub last: usize,
+
+    /// The `smaller` table, with the lists it holds.
+    pub smaller: usize,
+
+    /// The intervals of a small index.
+    pub intervals: usize,
+}
+
+impl HeapSize {
+    /// The memory held by all of the components.
+    ///
+    pub fn total(&self) -> usize {
+        self.domain
+            + self.start
+            + self.parent
+            + self.left
+            + self.last
+            + self.smaller
+            + self.intervals
+    }
+}
+
+/// The greatest number of intervals covering any one position.
+fn max_depth(xs: &[Interval]) -> usize {
+    // Intervals are closed, so at a given position, starts are counted
+    // before ends.
+    let mut events: Vec<(u64, bool)> = xs
+        .iter()
+        .flat_map(|x| [(x.first, false), (x.last, true)])
+        .collect();
+    events.sort_unstable();
+    let mut depth = 0;
+    let mut res = 0;
+    for (_, end) in events {
+        if end {
+            depth -= 1;
+        } else {
+            depth += 1;
+            res = res.max(depth);
+        }
+    }
+    res
+}
+
+/// The length of the longest chain of intervals, each containing the next.
+fn nesting_depth(xs: &[Interval]) -> usize {
+    // In order of start, and longest first among those with the same
+    // start, a chain is a subsequence whose ends never increase, so find
+    // the longest such by patience sorting.
+    let mut xs: Vec<Interval> = xs.to_vec();
+    xs.sort_unstable_by_key(|x| (x.first, Reverse(x.last)));
+    let mut tails: Vec<Reverse<u64>> = Vec::new();
+    for x in xs.iter() {
+        let k = Reverse(x.last);
+        let i = tails.partition_point(|t| *t <= k);
+        if i == tails.len() {
+            tails.push(k);
+        } else {
+            tails[i] = k;
+        }
+    }
+    tails.len()
+}
+
+fn length_stats(xs: &[Interval]) -> Option<LengthStats> {
+    if xs.is_empty() {
+        return None;
+    }
+    let mut lens: Vec<u64> = xs.iter().map(|x| x.len()).collect();
+    lens.sort_unstable();
+    let total: f64 = lens.iter().map(|l| *l as f64).sum();
+    Some(LengthStats {
+        min: lens[0],
+        max: lens[lens.len() - 1],
+        mean: total / lens.len() as f64,
+        median: lens[(lens.len() - 1) / 2],
+    })
+}
+
+impl<D: DomainMap> Stabby<D> {
+    /// Gather statistics describing the intervals of the index and its
+    /// structure, for sizing and tuning.
+    ///
+    /// This recovers and sorts the intervals, so it takes time in
+    /// proportion to building the index.
+    ///
+    pub fn stats(&self) -> StabbyStats {
+        let xs = self.intervals();
+        // The domain holds 0 besides the endpoints, whether or not it is
+        // one of them.
+        let points = domain_points(&xs);
+        let zero_used = xs.iter().any(|x| x.first == 0);
+        let dense_size = match &self.repr {
+            Repr::Small(_) => 0,
+            Repr::Full { dense, .. } => dense.start.len(),
+        };
+        StabbyStats {
+            intervals: xs.len(),
+            endpoints: points.len() - !zero_used as usize,
+            small: matches!(self.repr, Repr::Small(_)),
+            dense_size,
+            max_depth: max_depth(&xs),
+            nesting_depth: nesting_depth(&xs),
+            lengths: length_stats(&xs),
+        }
+    }
+
+    /// Estimate the memory the index holds on the heap, by component.
+    ///
+    pub fn heap_size_bytes(&self) -> HeapSize {
+        match &self.repr {
+            Repr::Small(xs) => HeapSize {
+                intervals: xs.capacity() * size_of::<Interval>(),
+                ..HeapSize::default()
+            },
+            Repr::Full { domain, dense, .. } => {
+                let slot = size_of::<Option<DenseInterval>>();
+                let lists: usize = dense
+                    .smaller
+                    .values()
+                    .map(|v| v.capacity() * size_of::<DenseInterval>())
+                    .sum();
+                HeapSize {
+                    domain: domain.heap_size_bytes(),
+                    start: (dense.start.capacity() + dense.start2.capacity()) * slot,
+                    parent: map_heap_bytes(&dense.parent),
+                    left: map_heap_bytes(&dense.left),
+                    last: map_heap_bytes(&dense.last),
+                    smaller: map_heap_bytes(&dense.smaller) + lists,
+                    intervals: 0,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, many_intervals};
+
+    #[test]
+    fn test_stats() {
+        let s = Stabby::new(&intervals()).stats();
+        assert_eq!(s.intervals, 8);
+        assert_eq!(s.endpoints, 13);
+        assert!(s.small);
+        assert_eq!(s.dense_size, 0);
+        assert_eq!(s.max_depth, 6);
+        assert_eq!(s.nesting_depth, 4);
+        let lengths = s.lengths.unwrap();
+        assert_eq!((lengths.min, lengths.max, lengths.median), (11, 261, 21));
+        assert_eq!(lengths.mean, 723.0 / 8.0);
+
+        let empty = Stabby::new(&[]).stats();
+        assert_eq!(
+            (empty.intervals, empty.endpoints, empty.max_depth),
+            (0, 0, 0)
+        );
+        assert_eq!(empty.lengths, None);
+    }
+
+    #[test]
+    fn test_stats_full() {
+        // Intervals from 0, each nested in the one before.
+        let xs: Vec<Interval> = (0..40).map(|i| Interval::new(i, 100 - i)).collect();
+        let s = Stabby::new(&xs);
+        let st = s.stats();
+        assert!(!st.small);
+        assert_eq!(st.endpoints, 80);
+        assert_eq!(st.dense_size, 2 * 80);
+        assert_eq!(st.max_depth, 40);
+        assert_eq!(st.nesting_depth, 40);
+
+        let h = s.heap_size_bytes();
+        assert_eq!(h.intervals, 0);
+        assert!(h.domain >= 80 * size_of::<u64>());
+        assert!(h.start >= 2 * st.dense_size * size_of::<Option<DenseInterval>>());
+        assert_eq!(
+            h.total(),
+            h.domain + h.start + h.parent + h.left + h.last + h.smaller
+        );
+
+        let small = Stabby::new(&xs[..5]).heap_size_bytes();
+        assert_eq!(small.total(), small.intervals);
+        assert!(small.intervals >= 5 * size_of::<Interval>());
+    }
+
+    #[test]
+    fn test_stats_many() {
+        let xs = many_intervals();
+        let st = Stabby::new(&xs).stats();
+        assert!(!st.small);
+        assert_eq!(st.intervals, xs.len());
+        let mut points: Vec<u64> = xs.iter().flat_map(|x| [x.first, x.last]).collect();
+        points.sort_unstable();
+        points.dedup();
+        assert_eq!(st.endpoints, points.len());
+        let depth = (0..600)
+            .map(|q| xs.iter().filter(|x| x.first <= q && q <= x.last).count())
+            .max();
+        assert_eq!(Some(st.max_depth), depth);
+        let lengths = st.lengths.unwrap();
+        let mut ls: Vec<u64> = xs.iter().map(|x| x.last - x.first + 1).collect();
+        ls.sort_unstable();
+        assert_eq!((lengths.min, lengths.max), (ls[0], ls[ls.len() - 1]));
+        assert_eq!(lengths.median, ls[(ls.len() - 1) / 2]);
+    }
+}
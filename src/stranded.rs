@@ -0,0 +1,257 @@
+use alloc::{string::ToString, vec::Vec};
+use core::{fmt::Display, str::FromStr};
+
+use crate::{Interval, Stabby, StabbyError};
+
+/// The strand of a genomic feature.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strand {
+    /// The forward strand, written `+`.
+    Forward,
+    /// The reverse strand, written `-`.
+    Reverse,
+    /// A feature with no strand, or an unknown one, written `.`.
+    Unknown,
+}
+
+impl Strand {
+    /// The other strand, where the strand is known.
+    ///
+    pub fn opposite(&self) -> Strand {
+        match self {
+            Strand::Forward => Strand::Reverse,
+            Strand::Reverse => Strand::Forward,
+            Strand::Unknown => Strand::Unknown,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl Display for Strand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let c = match self {
+            Strand::Forward => "+",
+            Strand::Reverse => "-",
+            Strand::Unknown => ".",
+        };
+        f.write_str(c)
+    }
+}
+
+impl FromStr for Strand {
+    type Err = StabbyError;
+
+    fn from_str(s: &str) -> Result<Strand, StabbyError> {
+        match s {
+            "+" => Ok(Strand::Forward),
+            "-" => Ok(Strand::Reverse),
+            "." => Ok(Strand::Unknown),
+            _ => Err(StabbyError::Parse {
+                input: s.to_string(),
+                reason: "expected a strand, one of +, - or .",
+            }),
+        }
+    }
+}
+
+/// How the strand of a query restricts the intervals it finds.
+///
+/// Where the strand of either the query or an interval is unknown, the
+/// interval is found only when strand is ignored.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StrandMode {
+    /// Find intervals on either strand, or none.
+    #[default]
+    Ignore,
+    /// Find only intervals on the same strand as the query.
+    Same,
+    /// Find only intervals on the strand opposite the query.
+    Opposite,
+}
+
+impl StrandMode {
+    /// The strands an interval may have to be found by a query on the
+    /// given strand.
+    fn strands(&self, strand: Strand) -> &'static [Strand] {
+        const ALL: [Strand; 3] = [Strand::Forward, Strand::Reverse, Strand::Unknown];
+        match (self, strand) {
+            (StrandMode::Ignore, _) => &ALL,
+            (_, Strand::Unknown) => &[],
+            (StrandMode::Same, Strand::Forward) | (StrandMode::Opposite, Strand::Reverse) => {
+                &ALL[0..1]
+            }
+            (StrandMode::Same, Strand::Reverse) | (StrandMode::Opposite, Strand::Forward) => {
+                &ALL[1..2]
+            }
+        }
+    }
+}
+
+/// An index over intervals that each lie on a strand, whose queries may
+/// be restricted to the same strand as the query, or the opposite one.
+///
+/// The intervals of each strand are held in a [`Stabby`] of their own.
+///
+/// ```rust
+/// use stabby::{Interval, Strand, StrandMode, StrandedStabby};
+///
+/// let genes = [
+///     (Interval::new(100, 500), Strand::Forward),
+///     (Interval::new(300, 900), Strand::Reverse),
+/// ];
+/// let idx = StrandedStabby::new(&genes);
+/// assert_eq!(idx.stab(400, Strand::Forward, StrandMode::Ignore).len(), 2);
+/// assert_eq!(idx.stab(400, Strand::Forward, StrandMode::Opposite), vec![genes[1]]);
+/// ```
+///
+pub struct StrandedStabby {
+    strands: [Stabby; 3],
+}
+
+impl StrandedStabby {
+    /// Create a new index. The list must be in sorted order of interval,
+    /// and free of duplicates, though the same interval may appear on
+    /// more than one strand.
+    ///
+    pub fn new(xs: &[(Interval, Strand)]) -> StrandedStabby {
+        let on =
+            |s: Strand| -> Vec<Interval> { xs.iter().filter(|x| x.1 == s).map(|x| x.0).collect() };
+        StrandedStabby {
+            strands: [
+                Stabby::new(&on(Strand::Forward)),
+                Stabby::new(&on(Strand::Reverse)),
+                Stabby::new(&on(Strand::Unknown)),
+            ],
+        }
+    }
+
+    /// The index of the intervals on one strand.
+    ///
+    pub fn strand(&self, strand: Strand) -> &Stabby {
+        &self.strands[strand.index()]
+    }
+
+    /// The number of intervals in the index.
+    ///
+    pub fn len(&self) -> usize {
+        self.strands.iter().map(|s| s.len()).sum()
+    }
+
+    /// Test if the index holds no intervals.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Test if a position on a strand stabs any intervals.
+    ///
+    pub fn stabs(&self, q: u64, strand: Strand, mode: StrandMode) -> bool {
+        mode.strands(strand)
+            .iter()
+            .any(|s| self.strand(*s).stabs(q))
+    }
+
+    /// Retrieve the intervals stabbed by a position on a strand, in sorted
+    /// order, and by strand where the intervals are the same.
+    ///
+    pub fn stab(&self, q: u64, strand: Strand, mode: StrandMode) -> Vec<(Interval, Strand)> {
+        self.gather(strand, mode, |idx| idx.stab(q))
+    }
+
+    /// Find the intervals that intersect a query interval on a strand, in
+    /// sorted order, and by strand where the intervals are the same.
+    ///
+    pub fn stab_interval(
+        &self,
+        q: &Interval,
+        strand: Strand,
+        mode: StrandMode,
+    ) -> Vec<(Interval, Strand)> {
+        self.gather(strand, mode, |idx| idx.stab_interval(q))
+    }
+
+    fn gather<F: Fn(&Stabby) -> Vec<Interval>>(
+        &self,
+        strand: Strand,
+        mode: StrandMode,
+        f: F,
+    ) -> Vec<(Interval, Strand)> {
+        let strands = mode.strands(strand);
+        let mut res: Vec<(Interval, Strand)> = Vec::new();
+        for s in strands.iter() {
+            res.extend(f(self.strand(*s)).into_iter().map(|x| (x, *s)));
+        }
+        if strands.len() > 1 {
+            res.sort_unstable();
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn features() -> Vec<(Interval, Strand)> {
+        vec![
+            (Interval::new(10, 100), Strand::Forward),
+            (Interval::new(10, 100), Strand::Reverse),
+            (Interval::new(20, 30), Strand::Unknown),
+            (Interval::new(25, 150), Strand::Reverse),
+            (Interval::new(40, 50), Strand::Forward),
+            (Interval::new(210, 220), Strand::Forward),
+        ]
+    }
+
+    #[test]
+    fn test_strand() {
+        for s in [Strand::Forward, Strand::Reverse, Strand::Unknown] {
+            assert_eq!(s.to_string().parse::<Strand>().unwrap(), s);
+        }
+        assert_eq!(Strand::Forward.opposite(), Strand::Reverse);
+        assert_eq!(Strand::Unknown.opposite(), Strand::Unknown);
+        assert!("*".parse::<Strand>().is_err());
+    }
+
+    #[test]
+    fn test_stranded() {
+        let xs = features();
+        let idx = StrandedStabby::new(&xs);
+        assert_eq!(idx.len(), 6);
+        assert_eq!(idx.strand(Strand::Reverse).len(), 2);
+        for q in 0..230 {
+            for strand in [Strand::Forward, Strand::Reverse, Strand::Unknown] {
+                for mode in [StrandMode::Ignore, StrandMode::Same, StrandMode::Opposite] {
+                    let want: Vec<(Interval, Strand)> = xs
+                        .iter()
+                        .filter(|(x, s)| {
+                            x.first <= q + 5
+                                && q <= x.last
+                                && match mode {
+                                    StrandMode::Ignore => true,
+                                    StrandMode::Same => *s == strand && *s != Strand::Unknown,
+                                    StrandMode::Opposite => {
+                                        *s == strand.opposite() && *s != Strand::Unknown
+                                    }
+                                }
+                        })
+                        .copied()
+                        .collect();
+                    let qi = Interval::new(q, q + 5);
+                    assert_eq!(idx.stab_interval(&qi, strand, mode), want);
+                    let stabbed: Vec<(Interval, Strand)> =
+                        want.iter().filter(|(x, _)| x.first <= q).copied().collect();
+                    assert_eq!(idx.stab(q, strand, mode), stabbed);
+                    assert_eq!(idx.stabs(q, strand, mode), !stabbed.is_empty());
+                }
+            }
+        }
+    }
+}
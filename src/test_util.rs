@@ -0,0 +1,52 @@
+//! Fixtures and helpers shared by the unit tests.
+
+use alloc::vec::Vec;
+
+use crate::Interval;
+
+/// A small set of intervals with shared starts, nesting and a gap.
+///
+pub(crate) fn intervals() -> Vec<Interval> {
+    vec![
+        Interval::new(10, 100),
+        Interval::new(10, 200),
+        Interval::new(20, 30),
+        Interval::new(25, 150),
+        Interval::new(40, 50),
+        Interval::new(40, 60),
+        Interval::new(40, 300),
+        Interval::new(210, 220),
+    ]
+}
+
+/// Enough intervals, including those of [`intervals`], that an index
+/// over them is held in its full form rather than as a sorted list.
+///
+pub(crate) fn many_intervals() -> Vec<Interval> {
+    let mut xs: Vec<Interval> = (0..200u64)
+        .map(|i| Interval::new(i * 7 % 500, i * 7 % 500 + i % 40))
+        .collect();
+    xs.extend(intervals());
+    xs.sort();
+    xs.dedup();
+    xs
+}
+
+/// A small linear congruential generator, so that randomized tests are
+/// repeatable.
+///
+pub(crate) struct MiniRng {
+    x: u64,
+}
+
+impl MiniRng {
+    pub(crate) fn new(seed: u64) -> MiniRng {
+        MiniRng { x: seed }
+    }
+
+    pub(crate) fn rnd(&mut self) -> u64 {
+        self.x = self.x.wrapping_mul(2862933555777941757u64);
+        self.x = self.x.wrapping_add(3037000493u64);
+        self.x >> 16
+    }
+}
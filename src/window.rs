@@ -0,0 +1,205 @@
+use alloc::{collections::BinaryHeap, vec::Vec};
+use core::cmp::Reverse;
+
+use crate::{AppendStabby, Interval, StabbyError};
+
+/// An index over a sliding window of intervals, such as the sessions
+/// active over the last few hours of a log.
+///
+/// Intervals are appended in order of their start, as with
+/// [`AppendStabby`], and evicted once they end before a watermark that
+/// the caller advances. The evicted intervals are returned to the caller
+/// by [`evict`](WindowStabby::evict), and no longer appear in the results
+/// of queries, which are otherwise the same as those of
+/// [`Stabby`](crate::Stabby).
+///
+/// Evicted intervals are hidden from queries straight away, but the space
+/// they occupy is reclaimed only when they come to outnumber the live
+/// intervals, at which point the index is rebuilt.
+///
+#[derive(Default)]
+pub struct WindowStabby {
+    index: AppendStabby,
+    expiry: BinaryHeap<Reverse<(u64, u64)>>,
+    watermark: u64,
+    expired: usize,
+}
+
+impl WindowStabby {
+    /// Create a new, empty, window.
+    ///
+    pub fn new() -> WindowStabby {
+        WindowStabby::default()
+    }
+
+    /// The number of live intervals in the window.
+    ///
+    pub fn len(&self) -> usize {
+        self.expiry.len()
+    }
+
+    /// Test whether the window holds no live intervals.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.expiry.is_empty()
+    }
+
+    /// The current watermark: every live interval ends at or after it.
+    ///
+    pub fn watermark(&self) -> u64 {
+        self.watermark
+    }
+
+    /// Append an interval, returning `true` if it was added, or `false` if
+    /// it was already present or ends before the watermark.
+    ///
+    /// Returns [`StabbyError::OutOfOrder`], leaving the window unchanged, if
+    /// the interval starts before the last interval appended.
+    ///
+    pub fn push(&mut self, x: Interval) -> Result<bool, StabbyError> {
+        self.index.check_order(x.first)?;
+        if x.last < self.watermark {
+            return Ok(false);
+        }
+        let added = self.index.push(x)?;
+        if added {
+            self.expiry.push(Reverse((x.last, x.first)));
+        }
+        Ok(added)
+    }
+
+    /// Advance the watermark, evicting every interval that ends before it.
+    /// The evicted intervals are returned in sorted order.
+    ///
+    /// A watermark behind the current one has no effect.
+    ///
+    pub fn evict(&mut self, watermark: u64) -> Vec<Interval> {
+        if watermark <= self.watermark {
+            return Vec::new();
+        }
+        self.watermark = watermark;
+        let mut res: Vec<Interval> = Vec::new();
+        while let Some(Reverse((last, first))) = self.expiry.peek().copied() {
+            if last >= watermark {
+                break;
+            }
+            self.expiry.pop();
+            res.push(Interval::new(first, last));
+        }
+        res.sort_unstable();
+        self.expired += res.len();
+        if self.expired > self.expiry.len() {
+            self.compact();
+        }
+        res
+    }
+
+    /// Rebuild the index with only the live intervals.
+    fn compact(&mut self) {
+        let watermark = self.watermark;
+        self.index.retain(|x| x.last >= watermark);
+        self.expired = 0;
+    }
+
+    fn is_live(&self, x: &Interval) -> bool {
+        x.last >= self.watermark
+    }
+
+    /// Test if a position stabs any live intervals.
+    ///
+    pub fn stabs(&self, q: u64) -> bool {
+        // Any interval containing a position at or after the
+        // watermark necessarily ends after it.
+        if q >= self.watermark {
+            self.index.stabs(q)
+        } else {
+            self.index.stab(q).iter().any(|x| self.is_live(x))
+        }
+    }
+
+    /// Retrieve the list of live intervals that are stabbed by
+    /// the given position. The intervals are returned in
+    /// sorted order.
+    ///
+    pub fn stab(&self, q: u64) -> Vec<Interval> {
+        let mut res = self.index.stab(q);
+        if q < self.watermark {
+            res.retain(|x| self.is_live(x));
+        }
+        res
+    }
+
+    /// Find the list of live intervals that intersect the given query
+    /// interval. The results are returned in sorted order.
+    ///
+    pub fn stab_interval(&self, q: &Interval) -> Vec<Interval> {
+        let mut res = self.index.stab_interval(q);
+        if q.first < self.watermark {
+            res.retain(|x| self.is_live(x));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{test_util::MiniRng, Stabby};
+
+    #[test]
+    fn test_window() {
+        let mut w = WindowStabby::new();
+        let mut oracle: BTreeSet<Interval> = BTreeSet::new();
+        let mut rng = MiniRng::new(5);
+        let mut first = 0;
+        for i in 0..3000 {
+            first += rng.rnd() % 3;
+            let x = Interval::new(first, first + rng.rnd() % 40);
+            assert_eq!(w.push(x).unwrap(), oracle.insert(x));
+            if i % 100 == 99 {
+                let watermark = first.saturating_sub(25);
+                let expected: Vec<Interval> = oracle
+                    .iter()
+                    .filter(|x| x.last < watermark)
+                    .copied()
+                    .collect();
+                oracle.retain(|x| x.last >= watermark);
+                assert_eq!(w.evict(watermark), expected);
+                assert_eq!(w.len(), oracle.len());
+                let s = Stabby::new(&oracle.iter().copied().collect::<Vec<Interval>>());
+                for q in watermark.saturating_sub(50)..first + 50 {
+                    assert_eq!(w.stabs(q), s.stabs(q));
+                    assert_eq!(w.stab(q), s.stab(q));
+                    let qi = Interval::new(q, q + 7);
+                    assert_eq!(w.stab_interval(&qi), s.stab_interval(&qi));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_window_edges() {
+        let mut w = WindowStabby::new();
+        w.push(Interval::new(10, 20)).unwrap();
+        w.push(Interval::new(12, 30)).unwrap();
+        assert_eq!(w.evict(21), vec![Interval::new(10, 20)]);
+        assert!(w.evict(15).is_empty());
+        assert_eq!(w.watermark(), 21);
+        assert!(!w.push(Interval::new(14, 18)).unwrap());
+        assert!(w.push(Interval::new(5, 40)).is_err());
+        // Out of order, and also ending before the watermark.
+        assert!(matches!(
+            w.push(Interval::new(5, 8)),
+            Err(StabbyError::OutOfOrder {
+                previous: 12,
+                found: 5
+            })
+        ));
+        assert_eq!(w.stab(15), vec![Interval::new(12, 30)]);
+        assert_eq!(w.evict(100), vec![Interval::new(12, 30)]);
+        assert!(w.is_empty());
+        assert!(!w.stabs(25));
+    }
+}
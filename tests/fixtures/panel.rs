@@ -0,0 +1,78 @@
+/// Generated by stabby; do not edit.
+pub static PANEL: ::stabby::StaticStabby = ::stabby::StaticStabby::from_raw(
+    &[
+        0, 18, 20, 36, 37, 40, 50, 55,
+        70, 73, 74, 90, 92, 100, 110, 111,
+        120, 129, 140, 147, 148, 150, 166, 170,
+        184, 185, 190, 200, 203, 220, 221, 222,
+        240, 250, 258, 259, 260, 270, 277, 280,
+        290, 295, 296, 300, 310, 314, 320, 330,
+        332, 333, 340, 350, 351, 360, 369, 370,
+        380, 388, 400, 406, 407, 410, 425, 430,
+        443, 444, 450, 460, 462, 480, 481, 499,
+        500, 510, 530, 560, 580,
+    ],
+    &[
+        Some((0, 0)), None, Some((2, 4)), Some((2, 4)), Some((2, 4)), None, Some((6, 10)), Some((6, 10)),
+        Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((8, 12)), None, Some((14, 16)), Some((14, 16)),
+        Some((14, 16)), None, Some((18, 22)), Some((18, 22)), Some((20, 26)), Some((20, 26)), Some((20, 26)), Some((20, 26)),
+        Some((24, 32)), Some((24, 32)), Some((24, 32)), Some((24, 32)), Some((28, 36)), Some((28, 36)), Some((30, 42)), Some((30, 42)),
+        Some((30, 42)), Some((30, 42)), Some((34, 46)), Some((34, 46)), Some((34, 46)), Some((34, 46)), Some((38, 52)), Some((38, 52)),
+        Some((40, 54)), Some((40, 54)), Some((40, 54)), Some((40, 54)), Some((44, 58)), Some((44, 58)), Some((44, 58)), Some((44, 58)),
+        Some((48, 64)), Some((48, 64)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)),
+        Some((56, 74)), Some((56, 74)), Some((56, 74)), Some((56, 74)), Some((60, 80)), Some((60, 80)), Some((62, 86)), Some((62, 86)),
+        Some((64, 92)), Some((64, 92)), Some((64, 92)), Some((64, 92)), Some((68, 100)), Some((68, 100)), Some((70, 72)), Some((70, 72)),
+        Some((70, 72)), Some((68, 100)), Some((68, 100)), Some((68, 100)), Some((76, 78)), Some((76, 78)), Some((76, 78)), Some((68, 100)),
+        Some((68, 100)), Some((68, 100)), Some((82, 86)), Some((82, 86)), Some((84, 88)), Some((84, 88)), Some((84, 88)), Some((84, 88)),
+        Some((84, 88)), Some((68, 100)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((68, 100)),
+        Some((96, 102)), Some((96, 102)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)),
+        Some((104, 112)), Some((104, 112)), Some((104, 112)), Some((104, 112)), Some((108, 116)), Some((108, 116)), Some((110, 122)), Some((110, 122)),
+        Some((110, 122)), Some((110, 122)), Some((114, 126)), Some((114, 126)), Some((114, 126)), Some((114, 126)), Some((118, 132)), Some((118, 132)),
+        Some((120, 134)), Some((120, 134)), Some((120, 134)), Some((120, 134)), Some((124, 138)), Some((124, 138)), Some((124, 138)), Some((124, 138)),
+        Some((128, 144)), Some((128, 144)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)),
+        Some((136, 148)), Some((136, 148)), Some((136, 148)), Some((136, 148)), Some((140, 150)), Some((140, 150)), Some((142, 152)), Some((142, 152)),
+        Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)),
+        Some((142, 152)), None,
+    ],
+    &[
+        Some((0, 0)), Some((0, 0)), Some((2, 4)), Some((2, 4)), Some((2, 4)), Some((2, 4)), Some((6, 10)), Some((6, 10)),
+        Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((8, 12)), Some((14, 16)), Some((14, 16)),
+        Some((14, 16)), Some((14, 16)), Some((18, 22)), Some((18, 22)), Some((20, 26)), Some((20, 26)), Some((20, 26)), Some((20, 26)),
+        Some((24, 32)), Some((24, 32)), Some((24, 32)), Some((24, 32)), Some((28, 36)), Some((28, 36)), Some((30, 42)), Some((30, 42)),
+        Some((30, 42)), Some((30, 42)), Some((34, 46)), Some((34, 46)), Some((34, 46)), Some((34, 46)), Some((38, 52)), Some((38, 52)),
+        Some((40, 54)), Some((40, 54)), Some((40, 54)), Some((40, 54)), Some((44, 58)), Some((44, 58)), Some((44, 58)), Some((44, 58)),
+        Some((48, 64)), Some((48, 64)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)), Some((50, 66)),
+        Some((56, 74)), Some((56, 74)), Some((56, 74)), Some((56, 74)), Some((60, 80)), Some((60, 80)), Some((62, 86)), Some((62, 86)),
+        Some((64, 92)), Some((64, 92)), Some((64, 92)), Some((64, 92)), Some((68, 100)), Some((68, 100)), Some((70, 72)), Some((70, 72)),
+        Some((70, 72)), Some((70, 72)), Some((70, 72)), Some((70, 72)), Some((76, 78)), Some((76, 78)), Some((76, 78)), Some((76, 78)),
+        Some((76, 78)), Some((76, 78)), Some((82, 86)), Some((82, 86)), Some((84, 88)), Some((84, 88)), Some((84, 88)), Some((84, 88)),
+        Some((84, 88)), Some((84, 88)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((90, 94)), Some((90, 94)),
+        Some((96, 102)), Some((96, 102)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)), Some((98, 106)),
+        Some((104, 112)), Some((104, 112)), Some((104, 112)), Some((104, 112)), Some((108, 116)), Some((108, 116)), Some((110, 122)), Some((110, 122)),
+        Some((110, 122)), Some((110, 122)), Some((114, 126)), Some((114, 126)), Some((114, 126)), Some((114, 126)), Some((118, 132)), Some((118, 132)),
+        Some((120, 134)), Some((120, 134)), Some((120, 134)), Some((120, 134)), Some((124, 138)), Some((124, 138)), Some((124, 138)), Some((124, 138)),
+        Some((128, 144)), Some((128, 144)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)), Some((130, 146)),
+        Some((136, 148)), Some((136, 148)), Some((136, 148)), Some((136, 148)), Some((140, 150)), Some((140, 150)), Some((142, 152)), Some((142, 152)),
+        Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)), Some((142, 152)),
+        Some((142, 152)), Some((142, 152)),
+    ],
+    &[
+        ((0, 0), (0, 0)), ((2, 4), (0, 0)), ((6, 10), (0, 0)), ((8, 12), (0, 0)), ((14, 16), (0, 0)), ((18, 22), (0, 0)), ((20, 26), (0, 0)), ((24, 32), (0, 0)),
+        ((28, 36), (0, 0)), ((30, 42), (0, 0)), ((34, 46), (0, 0)), ((38, 52), (0, 0)), ((40, 54), (0, 0)), ((44, 58), (0, 0)), ((48, 64), (0, 0)), ((50, 66), (0, 0)),
+        ((56, 74), (0, 0)), ((60, 80), (0, 0)), ((62, 86), (0, 0)), ((64, 92), (0, 0)), ((68, 100), (0, 0)), ((70, 72), (68, 100)), ((76, 78), (68, 100)), ((82, 86), (68, 100)),
+        ((84, 88), (68, 100)), ((90, 94), (68, 100)), ((96, 102), (0, 0)), ((98, 106), (0, 0)), ((104, 112), (0, 0)), ((108, 116), (0, 0)), ((110, 122), (0, 0)), ((114, 126), (0, 0)),
+        ((118, 132), (0, 0)), ((120, 134), (0, 0)), ((124, 138), (0, 0)), ((128, 144), (0, 0)), ((130, 146), (0, 0)), ((136, 148), (0, 0)), ((140, 150), (0, 0)), ((142, 152), (0, 0)),
+    ],
+    &[
+        ((2, 4), (0, 0)), ((6, 10), (2, 4)), ((8, 12), (6, 10)), ((14, 16), (8, 12)), ((18, 22), (14, 16)), ((20, 26), (18, 22)), ((24, 32), (20, 26)), ((28, 36), (24, 32)),
+        ((30, 42), (28, 36)), ((34, 46), (30, 42)), ((38, 52), (34, 46)), ((40, 54), (38, 52)), ((44, 58), (40, 54)), ((48, 64), (44, 58)), ((50, 66), (48, 64)), ((56, 74), (50, 66)),
+        ((60, 80), (56, 74)), ((62, 86), (60, 80)), ((64, 92), (62, 86)), ((68, 100), (64, 92)), ((76, 78), (70, 72)), ((82, 86), (76, 78)), ((84, 88), (82, 86)), ((90, 94), (84, 88)),
+        ((96, 102), (68, 100)), ((98, 106), (96, 102)), ((104, 112), (98, 106)), ((108, 116), (104, 112)), ((110, 122), (108, 116)), ((114, 126), (110, 122)), ((118, 132), (114, 126)), ((120, 134), (118, 132)),
+        ((124, 138), (120, 134)), ((128, 144), (124, 138)), ((130, 146), (128, 144)), ((136, 148), (130, 146)), ((140, 150), (136, 148)), ((142, 152), (140, 150)),
+    ],
+    &[
+        ((0, 0), (142, 152)), ((68, 100), (90, 94)),
+    ],
+    &[],
+    &[],
+);
@@ -0,0 +1,32 @@
+use stabby::{Interval, Stabby};
+
+// Generated from `panel()` by `Stabby::static_source`. The test below checks
+// it is still what the library writes, so rewrite it if that changes.
+include!("fixtures/panel.rs");
+
+/// Enough intervals that the index is not held in its small form.
+fn panel() -> Vec<Interval> {
+    (0..40u64)
+        .map(|i| {
+            let first = i * 37 % 500;
+            Interval::new(first, first + i * 13 % 90)
+        })
+        .collect()
+}
+
+#[test]
+fn test_fixture_is_current() {
+    let source = Stabby::new(&panel()).static_source("PANEL").unwrap();
+    assert_eq!(source, include_str!("fixtures/panel.rs"));
+}
+
+#[test]
+fn test_generated_queries() {
+    let s = Stabby::new(&panel());
+    for q in 0..600 {
+        assert_eq!(PANEL.stabs(q), s.stabs(q));
+        assert_eq!(PANEL.stab(q), s.stab(q));
+        let qi = Interval::new(q, q + 23);
+        assert_eq!(PANEL.stab_interval(&qi), s.stab_interval(&qi));
+    }
+}